@@ -0,0 +1,27 @@
+//! Compiles the translations in `po/` to `.mo` catalogs in `OUT_DIR`, so the
+//! app is translated when it runs from the build tree, e.g. with `cargo run`.
+
+#[path = "src/po_file.rs"]
+mod po_file;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    println!("cargo:rerun-if-changed=po");
+    let locale_dir = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR")).join("locale");
+    let linguas = fs::read_to_string("po/LINGUAS").expect("po/LINGUAS lists the translations");
+    for language in linguas
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    {
+        let po = Path::new("po").join(format!("{}.po", language));
+        let text = fs::read_to_string(&po).unwrap_or_else(|err| panic!("{}: {}", po.display(), err));
+        let dir = locale_dir.join(language).join("LC_MESSAGES");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("passwordgenerator.mo"), po_file::to_mo(&po_file::parse(&text))).unwrap();
+    }
+    println!("cargo:rustc-env=BUILD_LOCALEDIR={}", locale_dir.display());
+}
@@ -0,0 +1,30 @@
+#!/bin/bash
+# Extracts the translatable messages from the files in POTFILES into the
+# template and merges it into every language in LINGUAS.
+# Needs GNU gettext 0.24 or newer, the first with Rust support, and the
+# AppStream ITS rules for the metainfo file.
+set -e
+
+PO_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
+REPO_ROOT="$(cd "${PO_DIR}/.." && pwd)"
+DOMAIN="passwordgenerator"
+
+cd "${REPO_ROOT}"
+
+xgettext \
+    --from-code=UTF-8 \
+    --add-comments=Translators \
+    --keyword=gettext \
+    --keyword=pgettext:1c,2 \
+    --package-name="${DOMAIN}" \
+    --msgid-bugs-address="https://github.com/danst0/passwordgenerator/issues" \
+    --files-from=po/POTFILES \
+    --output="po/${DOMAIN}.pot"
+
+for language in $(grep -v '^#' po/LINGUAS); do
+    if [[ -f "po/${language}.po" ]]; then
+        msgmerge --quiet --update --backup=none "po/${language}.po" "po/${DOMAIN}.pot"
+    else
+        msginit --no-translator --locale="${language}" --input="po/${DOMAIN}.pot" --output-file="po/${language}.po"
+    fi
+done
@@ -0,0 +1,108 @@
+//! The application object. It exports the D-Bus interfaces from
+//! `dbus_register`, which runs before the bus name is owned, so a call that
+//! activates the app never reaches a path that is not exported yet.
+
+use crate::logging::LOG_DOMAIN;
+use crate::{load_settings, localized_strings, search_provider, service};
+use adw::prelude::*;
+use adw::subclass::prelude::*;
+
+glib::wrapper! {
+    pub struct Application(ObjectSubclass<imp::Application>)
+        @extends adw::Application, gtk4::Application, gio::Application,
+        @implements gio::ActionGroup, gio::ActionMap;
+}
+
+impl Application {
+    pub fn new(application_id: &str, flags: gio::ApplicationFlags) -> Self {
+        glib::Object::builder()
+            .property("application-id", application_id)
+            .property("flags", flags)
+            .build()
+    }
+
+    /// Exports the Generator and search provider objects below `path`.
+    fn export_objects(&self, connection: &gio::DBusConnection, path: &str) -> Vec<gio::RegistrationId> {
+        let restart_inactivity_timeout = |app: &Application| {
+            let app_weak = app.downgrade();
+            move || {
+                if let Some(app) = app_weak.upgrade() {
+                    drop(app.hold());
+                }
+            }
+        };
+        let app_weak = self.downgrade();
+        let search_callbacks = search_provider::Callbacks {
+            load_settings,
+            on_call: Box::new(restart_inactivity_timeout(self)),
+            launch: Box::new(move || {
+                if let Some(app) = app_weak.upgrade() {
+                    app.activate();
+                }
+            }),
+        };
+
+        let results = [
+            (
+                service::INTERFACE_NAME,
+                service::register(connection, path, load_settings, restart_inactivity_timeout(self)),
+            ),
+            (
+                search_provider::INTERFACE_NAME,
+                search_provider::register(
+                    connection,
+                    &format!("{}{}", path, search_provider::OBJECT_PATH_SUFFIX),
+                    localized_strings().search_result_description,
+                    search_callbacks,
+                ),
+            ),
+        ];
+        let mut registrations = Vec::new();
+        for (interface, result) in results {
+            match result {
+                Ok(id) => registrations.push(id),
+                Err(err) => glib::g_warning!(LOG_DOMAIN, "Failed to export {}: {}", interface, err),
+            }
+        }
+        registrations
+    }
+}
+
+mod imp {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[derive(Default)]
+    pub struct Application {
+        registrations: RefCell<Vec<gio::RegistrationId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Application {
+        const NAME: &'static str = "PasswordGeneratorApplication";
+        type Type = super::Application;
+        type ParentType = adw::Application;
+    }
+
+    impl ObjectImpl for Application {}
+
+    impl ApplicationImpl for Application {
+        fn dbus_register(&self, connection: &gio::DBusConnection, object_path: &str) -> Result<(), glib::Error> {
+            self.parent_dbus_register(connection, object_path)?;
+            let registrations = self.obj().export_objects(connection, object_path);
+            *self.registrations.borrow_mut() = registrations;
+            Ok(())
+        }
+
+        fn dbus_unregister(&self, connection: &gio::DBusConnection, object_path: &str) {
+            for id in self.registrations.take() {
+                let _ = connection.unregister_object(id);
+            }
+            self.parent_dbus_unregister(connection, object_path);
+        }
+    }
+
+    impl GtkApplicationImpl for Application {}
+
+    impl AdwApplicationImpl for Application {}
+}
@@ -0,0 +1,210 @@
+//! Typing a password into the focused window, so it never touches the clipboard.
+//!
+//! The RemoteDesktop portal is preferred; it works on GNOME and KDE and inside
+//! the Flatpak sandbox. Elsewhere `wtype` (wlroots virtual keyboard) or
+//! `xdotool` (XTest) type it, reading the password from stdin so it never
+//! shows up in a process list.
+
+use crate::logging::LOG_DOMAIN;
+use crate::portal::{unexpected_reply, Portal};
+use crate::secret::SecretString;
+use glib::variant::ObjectPath;
+use glib::{ToVariant, Variant};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+const REMOTE_DESKTOP_INTERFACE: &str = "org.freedesktop.portal.RemoteDesktop";
+const KEYBOARD_DEVICE: u32 = 1;
+/// Keep the permission until the user revokes it, so the dialog only shows once.
+const PERSIST_UNTIL_REVOKED: u32 = 2;
+const KEY_RELEASED: u32 = 0;
+const KEY_PRESSED: u32 = 1;
+
+/// The X11 keysym for `c`: Latin-1 maps directly, anything else through the
+/// Unicode keysym range.
+fn keysym(c: char) -> i32 {
+    let code = u32::from(c);
+    if (0x20..=0x7e).contains(&code) || (0xa0..=0xff).contains(&code) {
+        code as i32
+    } else {
+        (0x0100_0000 | code) as i32
+    }
+}
+
+struct RemoteDesktop {
+    portal: Portal,
+    session: ObjectPath,
+    restore_token: Option<String>,
+}
+
+impl RemoteDesktop {
+    /// Starts a keyboard-only session, which asks the user for permission
+    /// unless `restore_token` is still valid.
+    async fn start(restore_token: Option<&str>) -> Result<Self, glib::Error> {
+        let portal = Portal::session().await?;
+        let session = portal.create_session(REMOTE_DESKTOP_INTERFACE).await?;
+        match Self::select_and_start(&portal, &session, restore_token).await {
+            Ok(restore_token) => Ok(Self {
+                portal,
+                session,
+                restore_token,
+            }),
+            Err(err) => {
+                let _ = portal.close_session(&session).await;
+                Err(err)
+            }
+        }
+    }
+
+    async fn select_and_start(
+        portal: &Portal,
+        session: &ObjectPath,
+        restore_token: Option<&str>,
+    ) -> Result<Option<String>, glib::Error> {
+        let mut options = HashMap::from([
+            ("types", KEYBOARD_DEVICE.to_variant()),
+            ("persist_mode", PERSIST_UNTIL_REVOKED.to_variant()),
+        ]);
+        if let Some(token) = restore_token {
+            options.insert("restore_token", token.to_variant());
+        }
+        portal
+            .request(REMOTE_DESKTOP_INTERFACE, "SelectDevices", vec![session.to_variant()], options)
+            .await?;
+
+        let results = portal
+            .request(
+                REMOTE_DESKTOP_INTERFACE,
+                "Start",
+                vec![session.to_variant(), "".to_variant()],
+                HashMap::new(),
+            )
+            .await?;
+        let devices = results
+            .get("devices")
+            .and_then(|devices| devices.get::<u32>())
+            .ok_or_else(|| unexpected_reply("Start"))?;
+        if devices & KEYBOARD_DEVICE == 0 {
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::PermissionDenied,
+                "Keyboard access was not granted",
+            ));
+        }
+        Ok(results.get("restore_token").and_then(|token| token.get::<String>()))
+    }
+
+    async fn type_text(&self, text: &SecretString) -> Result<(), glib::Error> {
+        for c in text.as_str().chars() {
+            for state in [KEY_PRESSED, KEY_RELEASED] {
+                let parameters = (
+                    self.session.clone(),
+                    HashMap::<String, Variant>::new(),
+                    keysym(c),
+                    state,
+                )
+                    .to_variant();
+                self.portal
+                    .call(REMOTE_DESKTOP_INTERFACE, "NotifyKeyboardKeysym", parameters)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Types with `wtype` on Wayland or `xdotool` on X11.
+fn type_with_tool(text: &SecretString) -> io::Result<()> {
+    let mut command = if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut command = Command::new("wtype");
+        command.arg("-");
+        command
+    } else if std::env::var_os("DISPLAY").is_some() {
+        let mut command = Command::new("xdotool");
+        command.args(["type", "--clearmodifiers", "--file", "-"]);
+        command
+    } else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "no display to type into"));
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(err) = stdin.write_all(text.as_str().as_bytes()) {
+            // Stop typing a partial password, and reap the tool
+            drop(stdin);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(err);
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "{} failed: {}",
+            command.get_program().to_string_lossy(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Waits `delay` and types `text` into whatever window has focus by then.
+///
+/// Returns the portal's restore token to pass on the next call, so the
+/// permission dialog is not shown again.
+pub async fn type_text(
+    text: SecretString,
+    delay: Duration,
+    restore_token: Option<String>,
+) -> Result<Option<String>, glib::Error> {
+    // Only fall back before anything was typed, so nothing is typed twice
+    let remote_desktop = match RemoteDesktop::start(restore_token.as_deref()).await {
+        Ok(remote_desktop) => Some(remote_desktop),
+        Err(err) if err.matches(gio::IOErrorEnum::Cancelled) => return Err(err),
+        Err(err) => {
+            glib::g_info!(LOG_DOMAIN, "RemoteDesktop portal unavailable, typing with a helper tool: {}", err);
+            None
+        }
+    };
+
+    glib::timeout_future(delay).await;
+
+    match remote_desktop {
+        Some(remote_desktop) => {
+            let typed = remote_desktop.type_text(&text).await;
+            if let Err(err) = remote_desktop.portal.close_session(&remote_desktop.session).await {
+                glib::g_debug!(LOG_DOMAIN, "Failed to close RemoteDesktop session: {}", err);
+            }
+            typed.map(|()| remote_desktop.restore_token.or(restore_token))
+        }
+        None => {
+            gio::spawn_blocking(move || type_with_tool(&text))
+                .await
+                .unwrap_or_else(|_| Err(io::Error::other("auto-type panicked")))
+                .map_err(|err| glib::Error::new(gio::IOErrorEnum::Failed, &err.to_string()))?;
+            Ok(restore_token)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keysyms_follow_x11_mapping() {
+        assert_eq!(keysym('a'), 0x61);
+        assert_eq!(keysym('Z'), 0x5a);
+        assert_eq!(keysym('-'), 0x2d);
+        assert_eq!(keysym('!'), 0x21);
+        assert_eq!(keysym('é'), 0xe9);
+        assert_eq!(keysym('€'), 0x0100_20ac);
+    }
+}
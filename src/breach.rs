@@ -0,0 +1,418 @@
+//! Offline check of passwords against Have I Been Pwned's Pwned Passwords data.
+//!
+//! Two sources are supported: the SHA-1 file produced by the official
+//! downloader (one `HASH:COUNT` line per breached password, ordered by hash),
+//! which is binary-searched in place, and a compact Bloom filter built from it
+//! with `passwordgenerator build-breach-filter`. Both stay on disk, only the
+//! lines or filter words a lookup needs are read.
+
+use std::f64::consts::LN_2;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+pub const BUILD_FILTER_COMMAND: &str = "build-breach-filter";
+const FILTER_MAGIC: &[u8; 8] = b"PWGBLM01";
+/// Magic, number of bits and number of hashes, followed by the filter words.
+const FILTER_HEADER_LEN: u64 = 8 + 8 + 4;
+const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+const DIGEST_LEN: usize = 20;
+
+type Digest = [u8; DIGEST_LEN];
+
+pub enum BreachDatabase {
+    HashList(PathBuf),
+    Filter(FilterFile),
+}
+
+impl BreachDatabase {
+    /// Opens either a Bloom filter (recognised by its header) or an ordered
+    /// hash list (recognised by its first line), and rejects anything else.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut magic = [0u8; FILTER_MAGIC.len()];
+        let is_filter = match file.read_exact(&mut magic) {
+            Ok(()) => &magic == FILTER_MAGIC,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => false,
+            Err(err) => return Err(err),
+        };
+
+        if is_filter {
+            return Ok(Self::Filter(FilterFile::open(file)?));
+        }
+        file.seek(SeekFrom::Start(0))?;
+        let mut first_line = Vec::new();
+        BufReader::new(file).read_until(b'\n', &mut first_line)?;
+        if !is_hash_list_line(&first_line) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "neither a Pwned Passwords hash list nor a breach filter",
+            ));
+        }
+        Ok(Self::HashList(path.to_path_buf()))
+    }
+
+    pub fn contains(&self, password: &str) -> io::Result<bool> {
+        let digest = sha1(password);
+        match self {
+            Self::HashList(path) => hash_list_contains(&mut File::open(path)?, &digest),
+            Self::Filter(filter) => filter.contains(&digest),
+        }
+    }
+}
+
+fn sha1(password: &str) -> Zeroizing<Digest> {
+    Zeroizing::new(sha1_smol::Sha1::from(password).digest().bytes())
+}
+
+/// Parses the hash from a `HASH:COUNT` (or bare `HASH`) line.
+fn parse_hash_line(line: &[u8]) -> Option<Digest> {
+    let hex = line.get(..DIGEST_LEN * 2)?;
+    if let Some(&next) = line.get(DIGEST_LEN * 2) {
+        if !matches!(next, b':' | b'\r' | b'\n') {
+            return None;
+        }
+    }
+
+    let mut digest = [0u8; DIGEST_LEN];
+    for (byte, pair) in digest.iter_mut().zip(hex.chunks(2)) {
+        let pair = std::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(digest)
+}
+
+/// Whether `line` looks like the downloader's output: 40 hex digits, a colon
+/// and the number of occurrences.
+fn is_hash_list_line(line: &[u8]) -> bool {
+    let count = match line.get(DIGEST_LEN * 2..).and_then(|rest| rest.strip_prefix(b":")) {
+        Some(count) => count.trim_ascii_end(),
+        None => return false,
+    };
+    parse_hash_line(line).is_some() && !count.is_empty() && count.iter().all(u8::is_ascii_digit)
+}
+
+/// Returns the first line starting at or after `offset` with its start and end offsets.
+fn line_at_or_after<R: Read + Seek>(
+    reader: &mut R,
+    offset: u64,
+) -> io::Result<Option<(u64, u64, Digest)>> {
+    // Starting one byte early tells us whether `offset` itself begins a line.
+    let seek_to = offset.saturating_sub(1);
+    reader.seek(SeekFrom::Start(seek_to))?;
+    let mut buffered = BufReader::new(reader);
+    let mut line = Vec::new();
+
+    let mut start = seek_to;
+    if offset > 0 {
+        start += buffered.read_until(b'\n', &mut line)? as u64;
+        line.clear();
+    }
+
+    let read = buffered.read_until(b'\n', &mut line)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    let digest = parse_hash_line(&line)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed hash line"))?;
+    Ok(Some((start, start + read as u64, digest)))
+}
+
+fn hash_list_contains<R: Read + Seek>(reader: &mut R, digest: &Digest) -> io::Result<bool> {
+    let mut low = 0;
+    let mut high = reader.seek(SeekFrom::End(0))?;
+
+    // Lines starting before `low` sort below the digest, lines starting at or after `high` above it.
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match line_at_or_after(reader, mid)? {
+            Some((start, end, hash)) if start < high => match hash.cmp(digest) {
+                std::cmp::Ordering::Equal => return Ok(true),
+                std::cmp::Ordering::Less => low = end,
+                std::cmp::Ordering::Greater => high = mid,
+            },
+            _ => high = mid,
+        }
+    }
+    Ok(false)
+}
+
+pub struct BloomFilter {
+    words: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    pub fn with_capacity(items: u64, false_positive_rate: f64) -> Self {
+        let items = items.max(1) as f64;
+        let num_bits = (-(items * false_positive_rate.ln()) / (LN_2 * LN_2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / items) * LN_2).round().max(1.0) as u32;
+        Self {
+            words: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    pub fn insert(&mut self, digest: &Digest) {
+        for bit in filter_positions(digest, self.num_bits, self.num_hashes) {
+            self.words[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn contains(&self, digest: &Digest) -> bool {
+        filter_positions(digest, self.num_bits, self.num_hashes)
+            .all(|bit| self.words[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(FILTER_MAGIC)?;
+        writer.write_all(&self.num_bits.to_le_bytes())?;
+        writer.write_all(&self.num_hashes.to_le_bytes())?;
+        for word in &self.words {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+        writer.flush()
+    }
+}
+
+/// SHA-1 output is already uniform, so two slices of it drive double hashing.
+fn filter_positions(digest: &Digest, num_bits: u64, num_hashes: u32) -> impl Iterator<Item = u64> {
+    let mut first = [0u8; 8];
+    let mut second = [0u8; 8];
+    first.copy_from_slice(&digest[..8]);
+    second.copy_from_slice(&digest[8..16]);
+    let h1 = u64::from_le_bytes(first);
+    let h2 = u64::from_le_bytes(second) | 1;
+    (0..num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+}
+
+/// A Bloom filter written by [`BloomFilter::write_to`], left on disk. Filters
+/// for the full breach data run into gigabytes, and a lookup only needs one
+/// word per hash.
+pub struct FilterFile {
+    file: File,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl FilterFile {
+    fn open(file: File) -> io::Result<Self> {
+        let mut header = [0u8; FILTER_HEADER_LEN as usize];
+        file.read_exact_at(&mut header, 0)?;
+        if &header[..FILTER_MAGIC.len()] != FILTER_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not a breach filter"));
+        }
+        let num_bits = u64::from_le_bytes(header[8..16].try_into().unwrap());
+        let num_hashes = u32::from_le_bytes(header[16..20].try_into().unwrap());
+        if num_bits == 0 || num_hashes == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "empty breach filter"));
+        }
+        if file.metadata()?.len() < FILTER_HEADER_LEN + num_bits.div_ceil(64) * 8 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated breach filter"));
+        }
+        Ok(Self {
+            file,
+            num_bits,
+            num_hashes,
+        })
+    }
+
+    fn contains(&self, digest: &Digest) -> io::Result<bool> {
+        let mut word = [0u8; 8];
+        for bit in filter_positions(digest, self.num_bits, self.num_hashes) {
+            self.file.read_exact_at(&mut word, FILTER_HEADER_LEN + bit / 64 * 8)?;
+            if u64::from_le_bytes(word) & (1 << (bit % 64)) == 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Builds a Bloom filter from a Pwned Passwords hash file, returning the number of hashes added.
+pub fn build_filter(input: &Path, output: &Path, false_positive_rate: f64) -> io::Result<u64> {
+    let count = BufReader::new(File::open(input)?).split(b'\n').count() as u64;
+    let mut filter = BloomFilter::with_capacity(count, false_positive_rate);
+
+    let mut added = 0;
+    for line in BufReader::new(File::open(input)?).split(b'\n') {
+        let line = line?;
+        if line.iter().all(u8::is_ascii_whitespace) {
+            continue;
+        }
+        let digest = parse_hash_line(&line)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed hash line"))?;
+        filter.insert(&digest);
+        added += 1;
+    }
+
+    filter.write_to(BufWriter::new(File::create(output)?))?;
+    Ok(added)
+}
+
+/// Entry point for `passwordgenerator build-breach-filter`, returning the process exit code.
+pub fn run_build_filter(args: &[String]) -> i32 {
+    let (input, output) = match args {
+        [input, output] | [input, output, _] => (Path::new(input), Path::new(output)),
+        _ => {
+            eprintln!(
+                "Usage: passwordgenerator {} <pwned-passwords-sha1.txt> <output.filter> [false-positive-rate]",
+                BUILD_FILTER_COMMAND
+            );
+            return 2;
+        }
+    };
+    let rate = match args.get(2).map(|rate| rate.parse::<f64>()) {
+        None => DEFAULT_FALSE_POSITIVE_RATE,
+        Some(Ok(rate)) if rate > 0.0 && rate < 1.0 => rate,
+        Some(_) => {
+            eprintln!("False-positive rate must be a number between 0 and 1");
+            return 2;
+        }
+    };
+
+    match build_filter(input, output, rate) {
+        Ok(added) => {
+            println!("Wrote {} hashes to {}", added, output.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("Failed to build breach filter: {}", err);
+            1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn hash_list(passwords: &[&str]) -> Vec<u8> {
+        let mut hashes: Vec<Digest> = passwords.iter().map(|p| *sha1(p)).collect();
+        hashes.sort();
+        hashes
+            .iter()
+            .enumerate()
+            .flat_map(|(count, hash)| {
+                let hex: String = hash.iter().map(|b| format!("{:02X}", b)).collect();
+                format!("{}:{}\r\n", hex, count + 1).into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn recognizes_hash_list_lines() {
+        assert!(is_hash_list_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004\r\n"));
+        assert!(is_hash_list_line(b"5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8:1"));
+        assert!(!is_hash_list_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8\n"));
+        assert!(!is_hash_list_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:\n"));
+        assert!(!is_hash_list_line(b"ZZAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:3\n"));
+        assert!(!is_hash_list_line(b"password123\n"));
+    }
+
+    #[test]
+    fn parses_pwned_passwords_lines() {
+        let expected = *sha1("password");
+        assert_eq!(
+            parse_hash_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004\r\n"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_hash_line(b"5baa61e4c9b93f3f0682250b6cf8331b7ee68fd8"),
+            Some(expected)
+        );
+        assert_eq!(parse_hash_line(b"5BAA61E4"), None);
+        assert_eq!(parse_hash_line(b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8X"), None);
+    }
+
+    #[test]
+    fn hash_list_search_finds_every_entry_and_nothing_else() {
+        let breached: Vec<String> = (0..200).map(|i| format!("breached-{}", i)).collect();
+        let refs: Vec<&str> = breached.iter().map(String::as_str).collect();
+        let mut data = Cursor::new(hash_list(&refs));
+
+        for password in &breached {
+            assert!(hash_list_contains(&mut data, &sha1(password)).unwrap(), "{}", password);
+        }
+        for i in 0..200 {
+            let password = format!("safe-{}", i);
+            assert!(!hash_list_contains(&mut data, &sha1(&password)).unwrap(), "{}", password);
+        }
+        assert!(!hash_list_contains(&mut Cursor::new(Vec::new()), &sha1("x")).unwrap());
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("passwordgenerator-breach-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn bloom_filter_round_trips_and_has_no_false_negatives() {
+        let mut filter = BloomFilter::with_capacity(1000, 0.01);
+        for i in 0..1000 {
+            filter.insert(&sha1(&format!("breached-{}", i)));
+        }
+        for i in 0..1000 {
+            assert!(filter.contains(&sha1(&format!("breached-{}", i))));
+        }
+
+        let dir = temp_dir("filter");
+        let path = dir.join("pwned.filter");
+        filter.write_to(File::create(&path).unwrap()).unwrap();
+        let on_disk = FilterFile::open(File::open(&path).unwrap()).unwrap();
+
+        for i in 0..1000 {
+            assert!(on_disk.contains(&sha1(&format!("breached-{}", i))).unwrap());
+        }
+        let false_positives = (0..10_000)
+            .filter(|i| {
+                let digest = sha1(&format!("safe-{}", i));
+                let found = on_disk.contains(&digest).unwrap();
+                assert_eq!(found, filter.contains(&digest));
+                found
+            })
+            .count();
+        assert!(false_positives < 300, "{} false positives", false_positives);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn open_detects_filter_and_hash_list_files() {
+        let dir = temp_dir("open");
+        let list = dir.join("pwned.txt");
+        let filter = dir.join("pwned.filter");
+        std::fs::write(&list, hash_list(&["password", "123456"])).unwrap();
+
+        assert_eq!(build_filter(&list, &filter, 0.01).unwrap(), 2);
+        for path in [&list, &filter] {
+            let database = BreachDatabase::open(path).unwrap();
+            assert!(database.contains("password").unwrap());
+            assert!(!database.contains("kx7Qd-T2mzp-9vLwe").unwrap());
+        }
+        assert!(matches!(BreachDatabase::open(&filter).unwrap(), BreachDatabase::Filter(_)));
+
+        let other = dir.join("notes.txt");
+        for content in [&b""[..], b"hello\n", b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8\n", b"5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:x\n"] {
+            std::fs::write(&other, content).unwrap();
+            let err = BreachDatabase::open(&other).err().expect("accepted a file that is no breach data");
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let mut bytes = std::fs::read(&filter).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        std::fs::write(&other, bytes).unwrap();
+        assert!(BreachDatabase::open(&other).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
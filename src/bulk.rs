@@ -0,0 +1,288 @@
+//! Generating batches of passwords and exporting them for password managers.
+
+use crate::secret::SecretString;
+use std::borrow::Cow;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+    Bitwarden,
+    OnePassword,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [
+        ExportFormat::Csv,
+        ExportFormat::Json,
+        ExportFormat::Bitwarden,
+        ExportFormat::OnePassword,
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "bitwarden" => Some(ExportFormat::Bitwarden),
+            "1password" => Some(ExportFormat::OnePassword),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Bitwarden => "Bitwarden (CSV)",
+            ExportFormat::OnePassword => "1Password (CSV)",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            _ => "csv",
+        }
+    }
+}
+
+pub struct BulkItem {
+    pub name: String,
+    pub username: String,
+    pub password: SecretString,
+}
+
+/// Pairs generated passwords with `usernames`; extra usernames extend the batch.
+pub fn generate_batch(
+    count: usize,
+    usernames: &[String],
+    mut generate: impl FnMut() -> SecretString,
+) -> Vec<BulkItem> {
+    (0..count.max(usernames.len()))
+        .map(|index| {
+            let username = usernames.get(index).cloned().unwrap_or_default();
+            let name = if username.is_empty() {
+                format!("Password {}", index + 1)
+            } else {
+                username.clone()
+            };
+            BulkItem {
+                name,
+                username,
+                password: generate(),
+            }
+        })
+        .collect()
+}
+
+/// Non-empty, trimmed lines of a username list.
+pub fn parse_usernames(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+pub fn render(format: ExportFormat, items: &[BulkItem]) -> Zeroizing<String> {
+    let mut out = Zeroizing::new(String::new());
+    match format {
+        ExportFormat::Csv => {
+            out.push_str("username,password\n");
+            for item in items {
+                push_csv_row(&mut out, &[&text_cell(&item.username), item.password.as_str()]);
+            }
+        }
+        ExportFormat::Json => {
+            let entries: Vec<serde_json::Value> = items
+                .iter()
+                .map(|item| {
+                    serde_json::json!({
+                        "username": item.username,
+                        "password": item.password.as_str(),
+                    })
+                })
+                .collect();
+            let json = Zeroizing::new(serde_json::to_string_pretty(&entries).unwrap_or_default());
+            out.push_str(&json);
+            out.push('\n');
+            for mut entry in entries {
+                zeroize_json(&mut entry);
+            }
+        }
+        ExportFormat::Bitwarden => {
+            out.push_str("folder,favorite,type,name,notes,fields,reprompt,login_uri,login_username,login_password,login_totp\n");
+            for item in items {
+                push_csv_row(
+                    &mut out,
+                    &[
+                        "",
+                        "",
+                        "login",
+                        &text_cell(&item.name),
+                        "",
+                        "",
+                        "0",
+                        "",
+                        &text_cell(&item.username),
+                        item.password.as_str(),
+                        "",
+                    ],
+                );
+            }
+        }
+        ExportFormat::OnePassword => {
+            out.push_str("Title,Website,Username,Password,Notes\n");
+            for item in items {
+                push_csv_row(
+                    &mut out,
+                    &[&text_cell(&item.name), "", &text_cell(&item.username), item.password.as_str(), ""],
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Writes `contents` to `path`, readable and writable by the owner only.
+pub fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // `mode` only applies to newly created files
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()
+}
+
+/// Prefixes `text` with `'` when a spreadsheet would read it as a formula.
+/// Passwords are left as they are, since they must import unchanged and are
+/// not typed in by the user.
+fn text_cell(text: &str) -> Cow<'_, str> {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+fn push_csv_row(out: &mut String, fields: &[&str]) {
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            for c in field.chars() {
+                if c == '"' {
+                    out.push('"');
+                }
+                out.push(c);
+            }
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push('\n');
+}
+
+fn zeroize_json(value: &mut serde_json::Value) {
+    use zeroize::Zeroize;
+    match value {
+        serde_json::Value::String(text) => text.zeroize(),
+        serde_json::Value::Object(map) => map.values_mut().for_each(zeroize_json),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<BulkItem> {
+        let mut passwords = ["a,b\"c", "plain"].into_iter();
+        generate_batch(2, &["alice".to_owned()], || SecretString::from(passwords.next().unwrap()))
+    }
+
+    #[test]
+    fn usernames_are_paired_and_extend_the_batch() {
+        let items = generate_batch(1, &parse_usernames("alice\n\n  bob \n"), || SecretString::from("x"));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].username, "bob");
+
+        let items = sample();
+        assert_eq!((items[0].name.as_str(), items[0].username.as_str()), ("alice", "alice"));
+        assert_eq!((items[1].name.as_str(), items[1].username.as_str()), ("Password 2", ""));
+    }
+
+    #[test]
+    fn formats_quote_fields_and_keep_their_headers() {
+        let items = sample();
+        assert_eq!(
+            render(ExportFormat::Csv, &items).as_str(),
+            "username,password\nalice,\"a,b\"\"c\"\n,plain\n"
+        );
+        assert_eq!(
+            render(ExportFormat::OnePassword, &items).lines().nth(2),
+            Some("Password 2,,,plain,")
+        );
+        assert_eq!(
+            render(ExportFormat::Bitwarden, &items).lines().nth(1),
+            Some(",,login,alice,,,0,,alice,\"a,b\"\"c\",")
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&render(ExportFormat::Json, &items)).unwrap();
+        assert_eq!(json[0]["password"], "a,b\"c");
+        assert_eq!(json[1]["username"], "");
+    }
+
+    #[test]
+    fn csv_cells_are_not_read_as_formulas() {
+        let items = generate_batch(1, &["=cmd|' /C calc'!A0".to_owned(), "@sum".to_owned()], || {
+            SecretString::from("-secret")
+        });
+        let csv = render(ExportFormat::Csv, &items);
+        assert_eq!(
+            csv.as_str(),
+            "username,password\n'=cmd|' /C calc'!A0,-secret\n'@sum,-secret\n"
+        );
+        assert_eq!(
+            render(ExportFormat::OnePassword, &items).lines().nth(1),
+            Some("'=cmd|' /C calc'!A0,,'=cmd|' /C calc'!A0,-secret,")
+        );
+    }
+
+    #[test]
+    fn exports_are_private_to_the_owner() {
+        let path = std::env::temp_dir().join(format!("passwordgenerator-bulk-{}.csv", std::process::id()));
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "username,password\n").unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "username,password\n");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn format_names_round_trip() {
+        for format in ExportFormat::ALL {
+            let name = match format {
+                ExportFormat::OnePassword => "1password".to_owned(),
+                _ => format!("{:?}", format),
+            };
+            assert_eq!(ExportFormat::parse(&name), Some(format));
+        }
+        assert_eq!(ExportFormat::parse("xml"), None);
+    }
+}
@@ -0,0 +1,194 @@
+//! A system-wide shortcut through the XDG GlobalShortcuts portal.
+//!
+//! The portal is called directly over D-Bus, so the shortcut also works
+//! inside the Flatpak sandbox and the desktop decides the final key binding.
+
+use crate::logging::LOG_DOMAIN;
+use crate::portal::{Portal, PORTAL_PATH};
+use glib::variant::ObjectPath;
+use glib::{ToVariant, Variant};
+use std::collections::HashMap;
+
+const SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+pub const SHORTCUT_ID: &str = "generate-and-copy";
+/// Only a suggestion; the user confirms or changes it in the portal dialog.
+const PREFERRED_TRIGGER: &str = "CTRL+ALT+P";
+
+pub struct GlobalShortcuts {
+    portal: Portal,
+}
+
+impl GlobalShortcuts {
+    pub async fn session() -> Result<Self, glib::Error> {
+        Ok(Self {
+            portal: Portal::session().await?,
+        })
+    }
+
+    pub fn with_connection(connection: gio::DBusConnection, bus_name: &str) -> Self {
+        Self {
+            portal: Portal::with_connection(connection, bus_name),
+        }
+    }
+
+    /// Creates a portal session, binds the shortcut and calls `on_activated`
+    /// every time it is pressed. The binding lasts as long as the connection.
+    pub async fn bind(&self, description: &str, on_activated: impl Fn() + 'static) -> Result<(), glib::Error> {
+        let session = self.portal.create_session(SHORTCUTS_INTERFACE).await?;
+
+        let session_for_signal = session.as_str().to_owned();
+        self.portal.connection().signal_subscribe(
+            Some(self.portal.bus_name()),
+            Some(SHORTCUTS_INTERFACE),
+            Some("Activated"),
+            Some(PORTAL_PATH),
+            None,
+            gio::DBusSignalFlags::NONE,
+            move |_, _, _, _, _, parameters| {
+                if let Some((session, id, _, _)) = parameters.get::<(ObjectPath, String, u64, HashMap<String, Variant>)>() {
+                    if session.as_str() == session_for_signal && id == SHORTCUT_ID {
+                        on_activated();
+                    }
+                }
+            },
+        );
+
+        let shortcut = HashMap::from([
+            ("description", description.to_variant()),
+            ("preferred_trigger", PREFERRED_TRIGGER.to_variant()),
+        ]);
+        let results = self
+            .portal
+            .request(
+                SHORTCUTS_INTERFACE,
+                "BindShortcuts",
+                vec![
+                    session.to_variant(),
+                    vec![(SHORTCUT_ID, shortcut)].to_variant(),
+                    "".to_variant(),
+                ],
+                HashMap::new(),
+            )
+            .await?;
+        let trigger = results
+            .get("shortcuts")
+            .and_then(|shortcuts| shortcuts.get::<Vec<(String, HashMap<String, Variant>)>>())
+            .and_then(|shortcuts| shortcuts.into_iter().find(|(id, _)| id == SHORTCUT_ID))
+            .and_then(|(_, properties)| properties.get("trigger_description").and_then(|t| t.get::<String>()));
+        glib::g_info!(
+            LOG_DOMAIN,
+            "Global shortcut bound to {}",
+            trigger.as_deref().unwrap_or("an unassigned trigger")
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::portal::{request_path, REQUEST_INTERFACE};
+    use crate::test_bus::{self, PrivateBus};
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    const SESSION_PATH: &str = "/org/freedesktop/portal/desktop/session/1_2/s1";
+
+    const MOCK_XML: &str = r#"
+        <node>
+          <interface name="org.freedesktop.portal.GlobalShortcuts">
+            <method name="CreateSession">
+              <arg type="a{sv}" direction="in"/><arg type="o" direction="out"/>
+            </method>
+            <method name="BindShortcuts">
+              <arg type="o" direction="in"/><arg type="a(sa{sv})" direction="in"/>
+              <arg type="s" direction="in"/><arg type="a{sv}" direction="in"/>
+              <arg type="o" direction="out"/>
+            </method>
+          </interface>
+        </node>"#;
+
+    /// Serves a minimal GlobalShortcuts portal that accepts every request and
+    /// records the shortcuts it was asked to bind.
+    fn serve(bus: &PrivateBus) -> Rc<RefCell<Vec<(String, HashMap<String, Variant>)>>> {
+        let bound = Rc::new(RefCell::new(Vec::new()));
+        let bound_for_portal = bound.clone();
+        let node = gio::DBusNodeInfo::for_xml(MOCK_XML).unwrap();
+        bus.server
+            .register_object(PORTAL_PATH, &node.lookup_interface(SHORTCUTS_INTERFACE).unwrap())
+            .method_call(move |connection, sender, _, _, method, parameters, invocation| {
+                let options_index = if method == "CreateSession" { 0 } else { 3 };
+                let options: HashMap<String, Variant> = parameters.child_value(options_index).get().unwrap();
+                let token: String = options["handle_token"].get().unwrap();
+                let request = request_path(sender, &token);
+
+                let results = match method {
+                    "CreateSession" => HashMap::from([("session_handle".to_owned(), SESSION_PATH.to_variant())]),
+                    _ => {
+                        let shortcuts: Vec<(String, HashMap<String, Variant>)> =
+                            parameters.child_value(1).get().unwrap();
+                        bound_for_portal.borrow_mut().extend(shortcuts);
+                        HashMap::new()
+                    }
+                };
+                invocation.return_value(Some(&(ObjectPath::try_from(request.as_str()).unwrap(),).to_variant()));
+                connection
+                    .emit_signal(
+                        Some(sender),
+                        &request,
+                        REQUEST_INTERFACE,
+                        "Response",
+                        Some(&(0u32, results).to_variant()),
+                    )
+                    .unwrap();
+            })
+            .build()
+            .unwrap();
+        bound
+    }
+
+    fn activate(bus: &PrivateBus, session: &str, id: &str) {
+        let session = ObjectPath::try_from(session).unwrap();
+        bus.server
+            .emit_signal(
+                bus.client.unique_name().as_deref(),
+                PORTAL_PATH,
+                SHORTCUTS_INTERFACE,
+                "Activated",
+                Some(&(session, id, 0u64, HashMap::<String, Variant>::new()).to_variant()),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn bound_shortcut_fires_only_for_its_session_and_id() {
+        test_bus::run(|bus, context| {
+            let bound = serve(bus);
+            let shortcuts = GlobalShortcuts::with_connection(bus.client.clone(), &bus.server_name());
+            let activations = Rc::new(Cell::new(0));
+            let activations_for_shortcut = activations.clone();
+            context
+                .block_on(shortcuts.bind("Generate and copy", move || {
+                    activations_for_shortcut.set(activations_for_shortcut.get() + 1)
+                }))
+                .unwrap();
+
+            assert_eq!(bound.borrow().len(), 1);
+            assert_eq!(bound.borrow()[0].0, SHORTCUT_ID);
+            assert_eq!(
+                bound.borrow()[0].1["description"].get::<String>().as_deref(),
+                Some("Generate and copy")
+            );
+
+            activate(bus, "/org/freedesktop/portal/desktop/session/1_2/other", SHORTCUT_ID);
+            activate(bus, SESSION_PATH, "something-else");
+            activate(bus, SESSION_PATH, SHORTCUT_ID);
+            while activations.get() == 0 {
+                context.iteration(true);
+            }
+            // Signals arrive in order, so the two mismatches were already skipped
+            assert_eq!(activations.get(), 1);
+        });
+    }
+}
@@ -0,0 +1,418 @@
+//! Translations through gettext.
+//!
+//! The English texts are the message ids. Translators work on the `.po` files
+//! in `po/`, which are compiled to `.mo` catalogs when the app is installed
+//! and by the build script for runs from the build tree; `po/update.sh`
+//! extracts new messages from the code and the desktop files.
+
+use crate::logging::LOG_DOMAIN;
+use gettextrs::LocaleCategory;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+pub const GETTEXT_PACKAGE: &str = "passwordgenerator";
+
+/// Where the compiled catalogs are installed. Builds for another prefix than
+/// the Flatpak's set `LOCALEDIR` at compile time.
+const LOCALEDIR: &str = match option_env!("LOCALEDIR") {
+    Some(dir) => dir,
+    None => "/app/share/locale",
+};
+
+/// The catalogs the build script compiled from `po/`.
+const BUILD_LOCALEDIR: &str = env!("BUILD_LOCALEDIR");
+
+/// The installed catalogs, or the build's own when they are not installed,
+/// e.g. for `cargo run`.
+fn locale_dir() -> &'static str {
+    let installed = LANGUAGES.iter().any(|(code, _)| {
+        Path::new(LOCALEDIR)
+            .join(code)
+            .join("LC_MESSAGES")
+            .join(format!("{}.mo", GETTEXT_PACKAGE))
+            .is_file()
+    });
+    if installed {
+        LOCALEDIR
+    } else {
+        BUILD_LOCALEDIR
+    }
+}
+
+/// Selects the language from the environment and loads its catalog. Must run
+/// before the first translated text is looked up.
+pub fn init() {
+    gettextrs::setlocale(LocaleCategory::LcAll, "");
+    let bound = gettextrs::bindtextdomain(GETTEXT_PACKAGE, locale_dir())
+        .and_then(|_| gettextrs::bind_textdomain_codeset(GETTEXT_PACKAGE, "UTF-8"))
+        .and_then(|_| gettextrs::textdomain(GETTEXT_PACKAGE));
+    if let Err(err) = bound {
+        glib::g_warning!(LOG_DOMAIN, "Failed to load translations: {}", err);
+    }
+}
+
+/// The languages with a catalog, plus English, by code and native name.
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("de", "Deutsch"),
+    ("en", "English"),
+    ("es", "Español"),
+    ("fr", "Français"),
+    ("it", "Italiano"),
+    ("ja", "日本語"),
+    ("sv", "Svenska"),
+];
+
+pub fn is_available(language: &str) -> bool {
+    LANGUAGES.iter().any(|(code, _)| *code == language)
+}
+
+/// The catalog picked with [`set_language`]; `None` follows the desktop.
+static SELECTED: Mutex<Option<MoCatalog>> = Mutex::new(None);
+
+/// Translates into `language` from now on instead of the desktop's language,
+/// or follows the desktop again for `None`.
+///
+/// The catalog is read here instead of pointing gettext at it through
+/// `LANGUAGE`, since changing the environment is not safe once GLib and GDBus
+/// run threads of their own.
+pub fn set_language(language: Option<&str>) {
+    let catalog = language.map(|language| {
+        let path = catalog_path(language);
+        MoCatalog::load(&path).unwrap_or_else(|err| {
+            // English has no catalog, its texts are the message ids
+            if language != "en" {
+                glib::g_warning!(LOG_DOMAIN, "Failed to load {}: {}", path.display(), err);
+            }
+            MoCatalog::default()
+        })
+    });
+    *SELECTED.lock().unwrap() = catalog;
+}
+
+fn catalog_path(language: &str) -> PathBuf {
+    Path::new(locale_dir())
+        .join(language)
+        .join("LC_MESSAGES")
+        .join(format!("{}.mo", GETTEXT_PACKAGE))
+}
+
+/// Where translations come from: the installed catalogs at runtime, the
+/// `.po` files themselves in tests.
+pub trait Catalog {
+    /// The translation of `msgid`, or `msgid` itself when there is none.
+    fn translate(&self, context: Option<&str>, msgid: &str) -> String;
+
+    /// The language of the translations, e.g. `de`, whose plural rules and
+    /// number format the templates follow.
+    fn language(&self) -> String;
+
+    fn gettext(&self, msgid: &str) -> &'static str {
+        intern(self.translate(None, msgid))
+    }
+
+    fn pgettext(&self, context: &str, msgid: &str) -> &'static str {
+        intern(self.translate(Some(context), msgid))
+    }
+}
+
+/// The catalog picked with [`set_language`], or else the ones bound by
+/// [`init`] for the desktop's language.
+pub struct Gettext;
+
+impl Catalog for Gettext {
+    fn translate(&self, context: Option<&str>, msgid: &str) -> String {
+        if let Some(catalog) = SELECTED.lock().unwrap().as_ref() {
+            return catalog.translate(context, msgid);
+        }
+        match context {
+            Some(context) => gettextrs::pgettext(context, msgid),
+            None => gettextrs::gettext(msgid),
+        }
+    }
+
+    fn language(&self) -> String {
+        if let Some(catalog) = SELECTED.lock().unwrap().as_ref() {
+            return catalog.language();
+        }
+        // The empty message id looks up the header of the catalog in use
+        header_language(&gettextrs::gettext(""))
+    }
+}
+
+/// A compiled `.mo` catalog read into memory. Without one, every text stays
+/// English.
+#[derive(Default)]
+struct MoCatalog {
+    /// Translations by message id, which is prefixed by its context and
+    /// `\u{4}` for messages with one, the way gettext stores them.
+    messages: HashMap<String, String>,
+}
+
+impl MoCatalog {
+    fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    fn parse(data: &[u8]) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "not a gettext catalog");
+        let magic = data.get(..4).ok_or_else(invalid)?;
+        let little_endian = match u32::from_le_bytes(magic.try_into().unwrap()) {
+            0x9504_12de => true,
+            0xde12_0495 => false,
+            _ => return Err(invalid()),
+        };
+        let word = |offset: usize| -> io::Result<usize> {
+            let bytes: [u8; 4] = data
+                .get(offset..offset + 4)
+                .ok_or_else(invalid)?
+                .try_into()
+                .unwrap();
+            let value = if little_endian { u32::from_le_bytes(bytes) } else { u32::from_be_bytes(bytes) };
+            Ok(value as usize)
+        };
+        // Each table entry is the length and offset of a string
+        let string = |table: usize, index: usize| -> io::Result<String> {
+            let (len, offset) = (word(table + index * 8)?, word(table + index * 8 + 4)?);
+            let bytes = data.get(offset..offset + len).ok_or_else(invalid)?;
+            String::from_utf8(bytes.to_vec()).map_err(|_| invalid())
+        };
+
+        let (count, originals, translations) = (word(8)?, word(12)?, word(16)?);
+        // Plural forms are separated by NUL; only the singular is used yet
+        let singular = |text: String| text.split('\0').next().unwrap_or_default().to_owned();
+        let mut messages = HashMap::with_capacity(count);
+        for index in 0..count {
+            messages.insert(singular(string(originals, index)?), singular(string(translations, index)?));
+        }
+        Ok(Self { messages })
+    }
+}
+
+impl Catalog for MoCatalog {
+    fn translate(&self, context: Option<&str>, msgid: &str) -> String {
+        let key = match context {
+            Some(context) => format!("{}\u{4}{}", context, msgid),
+            None => msgid.to_owned(),
+        };
+        self.messages.get(&key).cloned().unwrap_or_else(|| msgid.to_owned())
+    }
+
+    fn language(&self) -> String {
+        header_language(self.messages.get("").map_or("", String::as_str))
+    }
+}
+
+/// The primary language of a catalog header's `Language:` field, or English
+/// when there is no catalog.
+fn header_language(header: &str) -> String {
+    header
+        .lines()
+        .find_map(|line| line.strip_prefix("Language:"))
+        .and_then(|language| language.trim().split(['_', '-', '.', '@']).next())
+        .filter(|language| !language.is_empty())
+        .unwrap_or("en")
+        .to_owned()
+}
+
+/// Keeps every distinct text alive for the rest of the run, so the UI can hold
+/// plain `&'static str`s. Looking the strings up again leaks nothing new.
+fn intern(text: String) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED.get_or_init(Default::default).lock().unwrap();
+    if let Some(&existing) = interned.get(text.as_str()) {
+        return existing;
+    }
+    let leaked: &'static str = Box::leak(text.into_boxed_str());
+    interned.insert(leaked);
+    leaked
+}
+
+/// Reading the `.po` files directly, for tests that check the translations.
+#[cfg(test)]
+pub mod po {
+    use super::{Catalog, GETTEXT_PACKAGE};
+    use crate::po_file::{self, PoEntry};
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::path::{Path, PathBuf};
+
+    /// The entries of a `.po` or `.pot` file without the header.
+    fn parse_po(text: &str) -> Vec<PoEntry> {
+        po_file::parse(text)
+            .into_iter()
+            .filter(|entry| !entry.msgid.is_empty())
+            .collect()
+    }
+
+    fn po_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("po").join(name)
+    }
+
+    /// The languages listed in `po/LINGUAS`.
+    pub fn languages() -> Vec<String> {
+        std::fs::read_to_string(po_path("LINGUAS"))
+            .unwrap()
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+            .collect()
+    }
+
+    /// A catalog read straight from a `.po` file, remembering every message it
+    /// had no translation for.
+    pub struct PoCatalog {
+        language: String,
+        messages: HashMap<(Option<String>, String), String>,
+        missing: RefCell<Vec<String>>,
+    }
+
+    impl PoCatalog {
+        fn from_entries(language: &str, entries: impl Iterator<Item = PoEntry>) -> Self {
+            Self {
+                language: language.to_owned(),
+                messages: entries.map(|entry| ((entry.context, entry.msgid), entry.msgstr)).collect(),
+                missing: Default::default(),
+            }
+        }
+
+        /// The translations of `language`, skipping fuzzy and empty ones.
+        pub fn load(language: &str) -> Self {
+            let text = std::fs::read_to_string(po_path(&format!("{}.po", language))).unwrap();
+            Self::from_entries(
+                language,
+                parse_po(&text)
+                    .into_iter()
+                    .filter(|entry| !entry.fuzzy && !entry.msgstr.is_empty()),
+            )
+        }
+
+        /// The untranslated messages of `po/passwordgenerator.pot`.
+        pub fn template() -> Self {
+            let text = std::fs::read_to_string(po_path(&format!("{}.pot", GETTEXT_PACKAGE))).unwrap();
+            Self::from_entries("en", parse_po(&text).into_iter().map(|entry| PoEntry {
+                msgstr: entry.msgid.clone(),
+                ..entry
+            }))
+        }
+
+        pub fn missing(&self) -> Vec<String> {
+            self.missing.borrow().clone()
+        }
+    }
+
+    impl Catalog for PoCatalog {
+        fn translate(&self, context: Option<&str>, msgid: &str) -> String {
+            let key = (context.map(str::to_owned), msgid.to_owned());
+            match self.messages.get(&key) {
+                Some(translation) => translation.clone(),
+                None => {
+                    self.missing.borrow_mut().push(msgid.to_owned());
+                    msgid.to_owned()
+                }
+            }
+        }
+
+        fn language(&self) -> String {
+            self.language.clone()
+        }
+    }
+
+    mod tests {
+        use super::*;
+        use crate::i18n::{MoCatalog, BUILD_LOCALEDIR};
+        use crate::template::{self, arguments};
+
+        #[test]
+        fn every_language_translates_every_message() {
+            let template = std::fs::read_to_string(po_path(&format!("{}.pot", GETTEXT_PACKAGE))).unwrap();
+            let template = parse_po(&template);
+            assert!(!template.is_empty());
+            for language in languages() {
+                let catalog = PoCatalog::load(&language);
+                let missing: Vec<&str> = template
+                    .iter()
+                    .filter(|entry| !catalog.messages.contains_key(&(entry.context.clone(), entry.msgid.clone())))
+                    .map(|entry| entry.msgid.as_str())
+                    .collect();
+                assert!(missing.is_empty(), "{} is missing translations for {:?}", language, missing);
+            }
+        }
+
+        #[test]
+        fn translations_keep_their_placeholders() {
+            for language in languages() {
+                for ((_, msgid), msgstr) in PoCatalog::load(&language).messages {
+                    assert_eq!(arguments(&msgid), arguments(&msgstr), "{}: {}", language, msgstr);
+                }
+            }
+        }
+
+        #[test]
+        fn language_list_matches_the_catalogs() {
+            let mut listed: Vec<&str> = crate::i18n::LANGUAGES
+                .iter()
+                .map(|(code, _)| *code)
+                .filter(|code| *code != "en")
+                .collect();
+            let mut catalogs = languages();
+            listed.sort_unstable();
+            catalogs.sort_unstable();
+            assert_eq!(listed, catalogs);
+        }
+
+        #[test]
+        fn plurals_cover_the_categories_of_their_language() {
+            for language in languages() {
+                for ((_, msgid), msgstr) in PoCatalog::load(&language).messages {
+                    let missing = template::missing_plural_categories(&language, &msgstr);
+                    assert!(missing.is_empty(), "{}: {:?} missing in {} for {}", language, missing, msgstr, msgid);
+                }
+            }
+        }
+
+        #[test]
+        fn compiled_catalogs_translate_like_the_po_files() {
+            for language in languages() {
+                let text = std::fs::read_to_string(po_path(&format!("{}.po", language))).unwrap();
+                let compiled = MoCatalog::parse(&po_file::to_mo(&po_file::parse(&text))).unwrap();
+                let built = MoCatalog::load(
+                    &Path::new(BUILD_LOCALEDIR)
+                        .join(&language)
+                        .join("LC_MESSAGES")
+                        .join(format!("{}.mo", GETTEXT_PACKAGE)),
+                )
+                .unwrap();
+                for catalog in [compiled, built] {
+                    assert_eq!(catalog.language(), language);
+                    for ((context, msgid), msgstr) in &PoCatalog::load(&language).messages {
+                        assert_eq!(catalog.translate(context.as_deref(), msgid), *msgstr, "{}", language);
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn broken_catalogs_are_rejected() {
+            let text = std::fs::read_to_string(po_path("de.po")).unwrap();
+            let mo = po_file::to_mo(&po_file::parse(&text));
+            assert!(MoCatalog::parse(&mo[..mo.len() / 2]).is_err());
+            assert!(MoCatalog::parse(b"").is_err());
+            assert!(MoCatalog::parse(b"msgid \"\"\nmsgstr \"\"\n").is_err());
+            assert_eq!(MoCatalog::default().translate(None, "Copy"), "Copy");
+            assert_eq!(MoCatalog::default().language(), "en");
+        }
+
+        #[test]
+        fn header_language_is_the_primary_language() {
+            let header = "Project-Id-Version: passwordgenerator\nLanguage: sv_SE.UTF-8\nPlural-Forms: nplurals=2;\n";
+            assert_eq!(crate::i18n::header_language(header), "sv");
+            assert_eq!(crate::i18n::header_language("Language: ja\n"), "ja");
+            assert_eq!(crate::i18n::header_language("Language: \n"), "en");
+            assert_eq!(crate::i18n::header_language(""), "en");
+        }
+    }
+}
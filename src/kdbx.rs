@@ -0,0 +1,328 @@
+//! Appending entries to an existing KeePass (KDBX 4) database.
+
+use crate::secret::SecretString;
+use keepass::config::DatabaseVersion;
+use keepass::db::{Entry, Node, Value};
+use keepass::{Database, DatabaseKey};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub struct KdbxEntry {
+    pub title: String,
+    pub username: String,
+    pub url: String,
+    pub notes: String,
+}
+
+/// What unlocks the database: a master password, a key file, or both.
+pub struct KdbxKey<'a> {
+    pub password: Option<&'a SecretString>,
+    pub key_file: Option<&'a Path>,
+}
+
+impl KdbxKey<'_> {
+    fn database_key(&self) -> io::Result<DatabaseKey> {
+        let mut key = DatabaseKey::new();
+        if let Some(password) = self.password {
+            key = key.with_password(password.as_str());
+        }
+        if let Some(path) = self.key_file {
+            key = key.with_keyfile(&mut fs::File::open(path)?)?;
+        }
+        if key.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "a master password or key file is required",
+            ));
+        }
+        Ok(key)
+    }
+}
+
+/// Adds `entry` with `password` to the root group of the database at `path`.
+///
+/// keepass's KDBX 4 writer is still experimental and drops what it does not
+/// parse, so the database as it was before the first append is kept as
+/// `<name>.bak`, and later appends never replace that copy. The database is
+/// encrypted in memory first and must open again to exactly what was saved,
+/// so databases with content the writer loses are refused. It is then swapped
+/// in through a temporary file next to it, so a failed save never leaves a
+/// truncated file.
+pub fn append_entry(path: &Path, key: &KdbxKey, entry: &KdbxEntry, password: &SecretString) -> io::Result<()> {
+    let mut db = Database::open(&mut fs::File::open(path)?, key.database_key()?).map_err(invalid_data)?;
+    if !matches!(db.config.version, DatabaseVersion::KDB4(_)) {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "only KDBX 4 databases can be written",
+        ));
+    }
+
+    db.root.children.push(Node::Entry(new_entry(entry, password)));
+
+    let mut data = Vec::new();
+    db.save(&mut data, key.database_key()?).map_err(invalid_data)?;
+    check_saved(&data, key, &db)?;
+
+    keep_original(path)?;
+    let tmp = temp_path(path);
+    let replaced = replace_file(&tmp, path, &data);
+    if replaced.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    replaced
+}
+
+/// Opens the encrypted `data` again and checks it holds everything that is
+/// in `expected`.
+fn check_saved(data: &[u8], key: &KdbxKey, expected: &Database) -> io::Result<()> {
+    let saved = Database::open(&mut &data[..], key.database_key()?).map_err(|err| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("the saved database does not open again: {}", err))
+    })?;
+    let same = saved.root == expected.root
+        && saved.meta == expected.meta
+        && saved.header_attachments == expected.header_attachments
+        && saved.deleted_objects == expected.deleted_objects;
+    if !same {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "this database has content that could not be saved without losing it, so it was left unchanged",
+        ));
+    }
+    Ok(())
+}
+
+/// Copies the database to `<name>.bak` unless a backup is there already, so
+/// the backup stays the database as it was before the first append.
+fn keep_original(path: &Path) -> io::Result<()> {
+    let backup = backup_path(path);
+    let mut file = match fs::OpenOptions::new().write(true).create_new(true).open(&backup) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let copied = fs::set_permissions(&backup, fs::metadata(path)?.permissions())
+        .and_then(|()| io::copy(&mut fs::File::open(path)?, &mut file))
+        .and_then(|_| file.sync_all());
+    if copied.is_err() {
+        // A partial copy would otherwise be kept as the original for good
+        let _ = fs::remove_file(&backup);
+    }
+    copied
+}
+
+fn new_entry(fields: &KdbxEntry, password: &SecretString) -> Entry {
+    let mut entry = Entry::new();
+    for (name, value) in [
+        ("Title", &fields.title),
+        ("UserName", &fields.username),
+        ("URL", &fields.url),
+        ("Notes", &fields.notes),
+    ] {
+        entry.fields.insert(name.to_owned(), Value::Unprotected(value.clone()));
+    }
+    entry
+        .fields
+        .insert("Password".to_owned(), Value::Protected(password.as_str().into()));
+    entry
+}
+
+fn replace_file(tmp: &Path, path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(tmp)?;
+    fs::set_permissions(tmp, fs::metadata(path)?.permissions())?;
+    file.write_all(data)?;
+    file.sync_all()?;
+    fs::rename(tmp, path)
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+/// The copy of the database as it was before the first entry was added.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.bak", name))
+}
+
+fn invalid_data(err: impl std::fmt::Display) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keepass::config::{DatabaseConfig, KdfConfig};
+
+    struct Fixture {
+        dir: PathBuf,
+        database: PathBuf,
+        key_file: PathBuf,
+    }
+
+    impl Fixture {
+        /// Creates an empty KDBX 4 database with a fast KDF so the tests stay quick.
+        fn new(name: &str, master: &SecretString) -> Self {
+            let dir = std::env::temp_dir().join(format!("passwordgenerator-kdbx-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let database = dir.join("fixture.kdbx");
+            let key_file = dir.join("fixture.keyx");
+            fs::write(&key_file, b"fixture key file contents that are neither hex nor XML").unwrap();
+
+            let config = DatabaseConfig {
+                kdf_config: KdfConfig::Aes { rounds: 1000 },
+                ..Default::default()
+            };
+            let key = KdbxKey {
+                password: Some(master),
+                key_file: Some(&key_file),
+            };
+            Database::new(config)
+                .save(&mut fs::File::create(&database).unwrap(), key.database_key().unwrap())
+                .unwrap();
+            Self { dir, database, key_file }
+        }
+
+        fn key<'a>(&'a self, master: &'a SecretString) -> KdbxKey<'a> {
+            KdbxKey {
+                password: Some(master),
+                key_file: Some(&self.key_file),
+            }
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    fn sample_entry() -> KdbxEntry {
+        KdbxEntry {
+            title: "Example".to_owned(),
+            username: "alice".to_owned(),
+            url: "https://example.com".to_owned(),
+            notes: "created by the tests".to_owned(),
+        }
+    }
+
+    #[test]
+    fn appended_entry_is_readable_with_the_same_key() {
+        let master = SecretString::from("correct horse");
+        let fixture = Fixture::new("append", &master);
+        let password = SecretString::from("kx7Qd-T2mzp-9vLwe");
+        let before = fs::read(&fixture.database).unwrap();
+
+        append_entry(&fixture.database, &fixture.key(&master), &sample_entry(), &password).unwrap();
+        assert_eq!(fs::read(backup_path(&fixture.database)).unwrap(), before);
+
+        let key = fixture.key(&master).database_key().unwrap();
+        let db = Database::open(&mut fs::File::open(&fixture.database).unwrap(), key).unwrap();
+        let entries: Vec<&Entry> = db
+            .root
+            .children
+            .iter()
+            .filter_map(|node| match node {
+                Node::Entry(entry) => Some(entry),
+                Node::Group(_) => None,
+            })
+            .collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].get_title(), Some("Example"));
+        assert_eq!(entries[0].get_username(), Some("alice"));
+        assert_eq!(entries[0].get_url(), Some("https://example.com"));
+        assert_eq!(entries[0].get("Notes"), Some("created by the tests"));
+        assert_eq!(entries[0].get_password(), Some("kx7Qd-T2mzp-9vLwe"));
+        assert!(matches!(entries[0].fields.get("Password"), Some(Value::Protected(_))));
+    }
+
+    #[test]
+    fn wrong_key_leaves_the_database_untouched() {
+        let master = SecretString::from("correct horse");
+        let fixture = Fixture::new("wrong-key", &master);
+        let before = fs::read(&fixture.database).unwrap();
+        let password = SecretString::from("kx7Qd-T2mzp-9vLwe");
+
+        let wrong = SecretString::from("battery staple");
+        assert!(append_entry(&fixture.database, &fixture.key(&wrong), &sample_entry(), &password).is_err());
+        let password_only = KdbxKey {
+            password: Some(&master),
+            key_file: None,
+        };
+        assert!(append_entry(&fixture.database, &password_only, &sample_entry(), &password).is_err());
+        let no_key = KdbxKey {
+            password: None,
+            key_file: None,
+        };
+        assert!(append_entry(&fixture.database, &no_key, &sample_entry(), &password).is_err());
+
+        assert_eq!(fs::read(&fixture.database).unwrap(), before);
+        assert!(!temp_path(&fixture.database).exists());
+        assert!(!backup_path(&fixture.database).exists());
+    }
+
+    #[test]
+    fn unwritable_folder_fails_without_touching_the_database() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let master = SecretString::from("correct horse");
+        let fixture = Fixture::new("read-only", &master);
+        let before = fs::read(&fixture.database).unwrap();
+        fs::set_permissions(&fixture.dir, fs::Permissions::from_mode(0o555)).unwrap();
+        let writable = fs::File::create(fixture.dir.join("probe")).is_ok();
+
+        let result = append_entry(
+            &fixture.database,
+            &fixture.key(&master),
+            &sample_entry(),
+            &SecretString::from("kx7Qd-T2mzp-9vLwe"),
+        );
+        fs::set_permissions(&fixture.dir, fs::Permissions::from_mode(0o755)).unwrap();
+        // Running as root ignores the permissions
+        if !writable {
+            assert_eq!(result.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+            assert_eq!(fs::read(&fixture.database).unwrap(), before);
+        }
+    }
+
+    #[test]
+    fn saved_database_is_checked_before_it_replaces_the_original() {
+        let master = SecretString::from("correct horse");
+        let fixture = Fixture::new("check", &master);
+        let key = fixture.key(&master);
+        let password = SecretString::from("kx7Qd-T2mzp-9vLwe");
+        let mut db = Database::open(&mut fs::File::open(&fixture.database).unwrap(), key.database_key().unwrap()).unwrap();
+        db.root.children.push(Node::Entry(new_entry(&sample_entry(), &password)));
+        let mut data = Vec::new();
+        db.save(&mut data, key.database_key().unwrap()).unwrap();
+
+        assert!(check_saved(&data, &key, &db).is_ok());
+        let wrong = SecretString::from("battery staple");
+        assert!(check_saved(&data, &fixture.key(&wrong), &db).is_err());
+
+        // Stands in for content the writer drops
+        let mut lost_notes = db.clone();
+        lost_notes.root.notes = Some("only in memory".to_owned());
+        assert!(check_saved(&data, &key, &lost_notes).is_err());
+        let mut lost_entry = db.clone();
+        lost_entry.root.children.push(Node::Entry(new_entry(&sample_entry(), &password)));
+        assert!(check_saved(&data, &key, &lost_entry).is_err());
+    }
+
+    #[test]
+    fn backup_keeps_the_database_from_before_the_first_append() {
+        let master = SecretString::from("correct horse");
+        let fixture = Fixture::new("backup", &master);
+        let original = fs::read(&fixture.database).unwrap();
+        let key = fixture.key(&master);
+
+        append_entry(&fixture.database, &key, &sample_entry(), &SecretString::from("first-password")).unwrap();
+        append_entry(&fixture.database, &key, &sample_entry(), &SecretString::from("second-password")).unwrap();
+
+        assert_eq!(fs::read(backup_path(&fixture.database)).unwrap(), original);
+        let db = Database::open(&mut fs::File::open(&fixture.database).unwrap(), key.database_key().unwrap()).unwrap();
+        assert_eq!(db.root.children.len(), 2);
+    }
+}
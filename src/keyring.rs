@@ -0,0 +1,366 @@
+//! Storing passwords in the freedesktop Secret Service (GNOME Keyring, KWallet, KeePassXC).
+//!
+//! The service is called directly over D-Bus using the `plain` session
+//! algorithm, so the secret only ever travels over the local session bus.
+
+use crate::logging::LOG_DOMAIN;
+use crate::secret::SecretString;
+use crate::APP_ID;
+use futures_channel::oneshot;
+use glib::variant::ObjectPath;
+use glib::{ToVariant, Variant};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+const SERVICE_NAME: &str = "org.freedesktop.secrets";
+const SERVICE_PATH: &str = "/org/freedesktop/secrets";
+const SERVICE_INTERFACE: &str = "org.freedesktop.Secret.Service";
+const COLLECTION_INTERFACE: &str = "org.freedesktop.Secret.Collection";
+const SESSION_INTERFACE: &str = "org.freedesktop.Secret.Session";
+const PROMPT_INTERFACE: &str = "org.freedesktop.Secret.Prompt";
+/// The null object path, used for "no prompt needed" and "no such collection".
+const NO_OBJECT: &str = "/";
+const SECRET_CONTENT_TYPE: &str = "text/plain; charset=utf8";
+
+pub struct KeyringEntry {
+    pub label: String,
+    pub username: String,
+    pub url: String,
+}
+
+impl KeyringEntry {
+    fn attributes(&self) -> HashMap<&'static str, &str> {
+        let mut attributes = HashMap::from([("application", APP_ID)]);
+        if !self.username.is_empty() {
+            attributes.insert("username", &self.username);
+        }
+        if !self.url.is_empty() {
+            attributes.insert("url", &self.url);
+        }
+        attributes
+    }
+}
+
+pub struct SecretService {
+    connection: gio::DBusConnection,
+    bus_name: String,
+}
+
+fn unexpected_reply(method: &str) -> glib::Error {
+    glib::Error::new(
+        gio::IOErrorEnum::InvalidData,
+        &format!("Unexpected reply to {} from the Secret Service", method),
+    )
+}
+
+impl SecretService {
+    pub async fn session() -> Result<Self, glib::Error> {
+        let connection = gio::bus_get_future(gio::BusType::Session).await?;
+        Ok(Self::with_connection(connection, SERVICE_NAME))
+    }
+
+    pub fn with_connection(connection: gio::DBusConnection, bus_name: &str) -> Self {
+        Self {
+            connection,
+            bus_name: bus_name.to_string(),
+        }
+    }
+
+    async fn call<T: glib::FromVariant>(
+        &self,
+        path: &str,
+        interface: &str,
+        method: &str,
+        parameters: Variant,
+    ) -> Result<T, glib::Error> {
+        let reply = self
+            .connection
+            .call_future(
+                Some(&self.bus_name),
+                path,
+                interface,
+                method,
+                Some(&parameters),
+                None,
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await?;
+        reply.get::<T>().ok_or_else(|| unexpected_reply(method))
+    }
+
+    /// Stores `secret` as a new item in the default collection, replacing an item with the same attributes.
+    pub async fn store(
+        &self,
+        entry: &KeyringEntry,
+        secret: &SecretString,
+        window_id: &str,
+    ) -> Result<(), glib::Error> {
+        let (_, session): (Variant, ObjectPath) = self
+            .call(
+                SERVICE_PATH,
+                SERVICE_INTERFACE,
+                "OpenSession",
+                ("plain", "".to_variant()).to_variant(),
+            )
+            .await?;
+
+        let result = self.store_in_session(&session, entry, secret, window_id).await;
+
+        let closed: Result<(), glib::Error> = self
+            .call(session.as_str(), SESSION_INTERFACE, "Close", ().to_variant())
+            .await;
+        if let Err(err) = closed {
+            glib::g_debug!(LOG_DOMAIN, "Failed to close Secret Service session: {}", err);
+        }
+        result
+    }
+
+    async fn store_in_session(
+        &self,
+        session: &ObjectPath,
+        entry: &KeyringEntry,
+        secret: &SecretString,
+        window_id: &str,
+    ) -> Result<(), glib::Error> {
+        let (collection,): (ObjectPath,) = self
+            .call(SERVICE_PATH, SERVICE_INTERFACE, "ReadAlias", ("default",).to_variant())
+            .await?;
+        if collection.as_str() == NO_OBJECT {
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::NotFound,
+                "The keyring has no default collection",
+            ));
+        }
+
+        let (_, prompt): (Vec<ObjectPath>, ObjectPath) = self
+            .call(
+                SERVICE_PATH,
+                SERVICE_INTERFACE,
+                "Unlock",
+                (vec![collection.clone()],).to_variant(),
+            )
+            .await?;
+        self.prompt(&prompt, window_id).await?;
+
+        let properties = HashMap::from([
+            ("org.freedesktop.Secret.Item.Label", entry.label.to_variant()),
+            ("org.freedesktop.Secret.Item.Attributes", entry.attributes().to_variant()),
+        ]);
+        // Built from the raw bytes so no intermediate Vec copy of the password is left behind
+        let secret = Variant::tuple_from_iter([
+            session.to_variant(),
+            Vec::<u8>::new().to_variant(),
+            Variant::array_from_fixed_array(secret.as_str().as_bytes()),
+            SECRET_CONTENT_TYPE.to_variant(),
+        ]);
+        let parameters = Variant::tuple_from_iter([properties.to_variant(), secret, true.to_variant()]);
+        let (_, prompt): (ObjectPath, ObjectPath) = self
+            .call(collection.as_str(), COLLECTION_INTERFACE, "CreateItem", parameters)
+            .await?;
+        self.prompt(&prompt, window_id).await
+    }
+
+    /// Shows a Secret Service prompt (e.g. to unlock the keyring) and waits for it to complete.
+    async fn prompt(&self, prompt: &ObjectPath, window_id: &str) -> Result<(), glib::Error> {
+        if prompt.as_str() == NO_OBJECT {
+            return Ok(());
+        }
+
+        let (sender, receiver) = oneshot::channel::<bool>();
+        let sender = RefCell::new(Some(sender));
+        let subscription = self.connection.signal_subscribe(
+            Some(&self.bus_name),
+            Some(PROMPT_INTERFACE),
+            Some("Completed"),
+            Some(prompt.as_str()),
+            None,
+            gio::DBusSignalFlags::NONE,
+            move |_, _, _, _, _, parameters| {
+                if let Some((dismissed, _)) = parameters.get::<(bool, Variant)>() {
+                    if let Some(sender) = sender.borrow_mut().take() {
+                        let _ = sender.send(dismissed);
+                    }
+                }
+            },
+        );
+
+        let shown: Result<(), glib::Error> = self
+            .call(prompt.as_str(), PROMPT_INTERFACE, "Prompt", (window_id,).to_variant())
+            .await;
+        let dismissed = match shown {
+            Ok(()) => receiver.await.unwrap_or(true),
+            Err(err) => {
+                self.connection.signal_unsubscribe(subscription);
+                return Err(err);
+            }
+        };
+        self.connection.signal_unsubscribe(subscription);
+
+        if dismissed {
+            Err(glib::Error::new(
+                gio::IOErrorEnum::Cancelled,
+                "The keyring prompt was dismissed",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_bus::{self, PrivateBus};
+    use std::rc::Rc;
+
+    const COLLECTION_PATH: &str = "/org/freedesktop/secrets/collection/login";
+    const SESSION_PATH: &str = "/org/freedesktop/secrets/session/s1";
+    const PROMPT_PATH: &str = "/org/freedesktop/secrets/prompt/p1";
+
+    const MOCK_XML: &str = r#"
+        <node>
+          <interface name="org.freedesktop.Secret.Service">
+            <method name="OpenSession">
+              <arg type="s" direction="in"/><arg type="v" direction="in"/>
+              <arg type="v" direction="out"/><arg type="o" direction="out"/>
+            </method>
+            <method name="ReadAlias">
+              <arg type="s" direction="in"/><arg type="o" direction="out"/>
+            </method>
+            <method name="Unlock">
+              <arg type="ao" direction="in"/>
+              <arg type="ao" direction="out"/><arg type="o" direction="out"/>
+            </method>
+          </interface>
+          <interface name="org.freedesktop.Secret.Collection">
+            <method name="CreateItem">
+              <arg type="a{sv}" direction="in"/><arg type="(oayays)" direction="in"/>
+              <arg type="b" direction="in"/>
+              <arg type="o" direction="out"/><arg type="o" direction="out"/>
+            </method>
+          </interface>
+          <interface name="org.freedesktop.Secret.Session">
+            <method name="Close"/>
+          </interface>
+          <interface name="org.freedesktop.Secret.Prompt">
+            <method name="Prompt"><arg type="s" direction="in"/></method>
+          </interface>
+        </node>"#;
+
+    #[derive(Debug)]
+    struct StoredItem {
+        label: String,
+        attributes: HashMap<String, String>,
+        session: String,
+        secret: Vec<u8>,
+    }
+
+    /// Serves a minimal in-process Secret Service; `prompt` decides whether
+    /// CreateItem needs a prompt and if it gets dismissed.
+    fn serve(bus: &PrivateBus, prompt: Option<bool>) -> Rc<RefCell<Vec<StoredItem>>> {
+        let items = Rc::new(RefCell::new(Vec::new()));
+        let node = gio::DBusNodeInfo::for_xml(MOCK_XML).unwrap();
+        let objects = [
+            (SERVICE_PATH, SERVICE_INTERFACE),
+            (COLLECTION_PATH, COLLECTION_INTERFACE),
+            (SESSION_PATH, SESSION_INTERFACE),
+            (PROMPT_PATH, PROMPT_INTERFACE),
+        ];
+        for (path, interface) in objects {
+            let items = items.clone();
+            let info = node.lookup_interface(interface).unwrap();
+            bus.server
+                .register_object(path, &info)
+                .method_call(move |connection, _, _, _, method, parameters, invocation| {
+                    let path = |p: &str| ObjectPath::try_from(p).unwrap();
+                    let reply = match method {
+                        "OpenSession" => ("".to_variant(), path(SESSION_PATH)).to_variant(),
+                        "ReadAlias" => (path(COLLECTION_PATH),).to_variant(),
+                        "Unlock" => (Vec::<ObjectPath>::new(), path(NO_OBJECT)).to_variant(),
+                        "CreateItem" => {
+                            type Secret = (ObjectPath, Vec<u8>, Vec<u8>, String);
+                            let (properties, secret, _): (HashMap<String, Variant>, Secret, bool) =
+                                parameters.get().unwrap();
+                            items.borrow_mut().push(StoredItem {
+                                label: properties["org.freedesktop.Secret.Item.Label"].get().unwrap(),
+                                attributes: properties["org.freedesktop.Secret.Item.Attributes"]
+                                    .get()
+                                    .unwrap(),
+                                session: secret.0.to_string(),
+                                secret: secret.2,
+                            });
+                            let prompt = if prompt.is_some() { PROMPT_PATH } else { NO_OBJECT };
+                            (path(&format!("{}/i1", COLLECTION_PATH)), path(prompt)).to_variant()
+                        }
+                        "Prompt" => {
+                            let dismissed = prompt.unwrap_or(false);
+                            connection
+                                .emit_signal(
+                                    None,
+                                    PROMPT_PATH,
+                                    PROMPT_INTERFACE,
+                                    "Completed",
+                                    Some(&(dismissed, "".to_variant()).to_variant()),
+                                )
+                                .unwrap();
+                            ().to_variant()
+                        }
+                        _ => ().to_variant(),
+                    };
+                    invocation.return_value(Some(&reply));
+                })
+                .build()
+                .unwrap();
+        }
+        items
+    }
+
+    fn service(bus: &PrivateBus) -> SecretService {
+        SecretService::with_connection(bus.client.clone(), &bus.server_name())
+    }
+
+    fn entry() -> KeyringEntry {
+        KeyringEntry {
+            label: "Example account".to_string(),
+            username: "alice".to_string(),
+            url: "https://example.com".to_string(),
+        }
+    }
+
+    #[test]
+    fn stores_item_with_attributes_in_default_collection() {
+        test_bus::run(|bus, context| {
+            let items = serve(bus, None);
+            let secret = SecretString::from("kx7Qd-T2mzp-9vLwe");
+            context.block_on(service(bus).store(&entry(), &secret, "")).unwrap();
+
+            let items = items.borrow();
+            assert_eq!(items.len(), 1);
+            let item = &items[0];
+            assert_eq!(item.label, "Example account");
+            assert_eq!(item.attributes["username"], "alice");
+            assert_eq!(item.attributes["url"], "https://example.com");
+            assert_eq!(item.attributes["application"], APP_ID);
+            assert_eq!(item.session, SESSION_PATH);
+            assert_eq!(item.secret, b"kx7Qd-T2mzp-9vLwe");
+        });
+    }
+
+    #[test]
+    fn completed_prompt_succeeds_and_dismissed_prompt_fails() {
+        test_bus::run(|bus, context| {
+            serve(bus, Some(false));
+            let secret = SecretString::from("secret");
+            assert!(context.block_on(service(bus).store(&entry(), &secret, "")).is_ok());
+        });
+        test_bus::run(|bus, context| {
+            serve(bus, Some(true));
+            let secret = SecretString::from("secret");
+            let err = context
+                .block_on(service(bus).store(&entry(), &secret, ""))
+                .unwrap_err();
+            assert!(err.matches(gio::IOErrorEnum::Cancelled));
+        });
+    }
+}
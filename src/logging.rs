@@ -0,0 +1,77 @@
+//! Leveled logging routed through GLib's log handlers.
+//!
+//! Info messages describe non-sensitive events (a password was generated or
+//! copied) and are always written to stderr. Debug messages are only written
+//! when `PASSWORDGENERATOR_DEBUG` is set or the app is started with `--debug`.
+//! No level may ever include password contents.
+
+use glib::{LogLevel, LogLevels};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub const LOG_DOMAIN: &str = "passwordgenerator";
+pub const DEBUG_ENV_VAR: &str = "PASSWORDGENERATOR_DEBUG";
+
+static DEBUG_ENABLED: AtomicBool = AtomicBool::new(false);
+
+pub fn init() {
+    if env_enables_debug(std::env::var(DEBUG_ENV_VAR).ok().as_deref()) {
+        set_debug_enabled(true);
+    }
+
+    glib::log_set_handler(
+        Some(LOG_DOMAIN),
+        LogLevels::all(),
+        false,
+        false,
+        |domain, level, message| {
+            if should_log(level, DEBUG_ENABLED.load(Ordering::Relaxed)) {
+                eprintln!("{}-{}: {}", domain.unwrap_or(LOG_DOMAIN), level_name(level), message);
+            }
+        },
+    );
+}
+
+pub fn set_debug_enabled(enabled: bool) {
+    DEBUG_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn env_enables_debug(value: Option<&str>) -> bool {
+    matches!(value, Some(value) if !value.is_empty() && value != "0")
+}
+
+fn should_log(level: LogLevel, debug_enabled: bool) -> bool {
+    level != LogLevel::Debug || debug_enabled
+}
+
+fn level_name(level: LogLevel) -> &'static str {
+    match level {
+        LogLevel::Error => "ERROR",
+        LogLevel::Critical => "CRITICAL",
+        LogLevel::Warning => "WARNING",
+        LogLevel::Message => "MESSAGE",
+        LogLevel::Info => "INFO",
+        LogLevel::Debug => "DEBUG",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_messages_are_opt_in() {
+        assert!(should_log(LogLevel::Info, false));
+        assert!(should_log(LogLevel::Warning, false));
+        assert!(!should_log(LogLevel::Debug, false));
+        assert!(should_log(LogLevel::Debug, true));
+    }
+
+    #[test]
+    fn env_var_values_enable_debug() {
+        assert!(!env_enables_debug(None));
+        assert!(!env_enables_debug(Some("")));
+        assert!(!env_enables_debug(Some("0")));
+        assert!(env_enables_debug(Some("1")));
+        assert!(env_enables_debug(Some("yes")));
+    }
+}
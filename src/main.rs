@@ -1,31 +1,99 @@
+mod application;
+mod autotype;
+mod breach;
+mod bulk;
+mod global_shortcut;
+mod i18n;
+mod kdbx;
+mod keyring;
+mod logging;
+mod pass;
+#[cfg(test)]
+mod po_file;
+mod portal;
+mod qr;
+mod search_provider;
+mod secret;
+mod service;
+mod strength;
+mod template;
+#[cfg(test)]
+mod test_bus;
+
 use gtk4 as gtk;
-use gtk::prelude::*;
-use gtk::{Adjustment, Application, ApplicationWindow, Button, CheckButton, CssProvider, Entry, FlowBox, GestureClick, Label, Orientation, PropagationPhase, Revealer, RevealerTransitionType, SelectionMode, SpinButton};
-use gio::{Settings, SettingsSchemaSource, SimpleAction};
+use adw::prelude::*;
+use adw::ApplicationWindow;
+use gtk::{Adjustment, Button, CheckButton, CssProvider, Entry, GestureClick, Label, Orientation, PropagationPhase, Revealer, RevealerTransitionType, SpinButton, ToggleButton};
+use gio::SimpleAction;
 use rand::{seq::SliceRandom, Rng};
 use std::cell::{Cell, RefCell};
 use std::rc::Rc;
-use std::sync::Once;
 use glib::{prelude::Cast, source::SourceId};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use application::Application;
+use breach::BreachDatabase;
+use bulk::ExportFormat;
+use global_shortcut::GlobalShortcuts;
+use i18n::Catalog;
+use kdbx::{KdbxEntry, KdbxKey};
+use keyring::{KeyringEntry, SecretService};
+use logging::LOG_DOMAIN;
+use pass::PasswordStore;
+use secret::SecretString;
+use strength::{CrackTime, Feedback};
+use template::Arg;
+use zeroize::Zeroizing;
 
 const APP_ID: &str = "io.github.danst0.passwordgenerator";
+/// Copy and clear notifications share an id, so only the latest one is shown.
+const CLIPBOARD_NOTIFICATION_ID: &str = "clipboard";
 const DEFAULT_GROUPS: i32 = 3;
-const CLOSE_AFTER_SEC: i32 = 10;
+const MAX_GROUPS: i32 = 10;
+const DEFAULT_AUTO_CLOSE_SEC: i32 = 10;
+const MAX_AUTO_CLOSE_SEC: i32 = 300;
+const MAX_CLEAR_CLIPBOARD_SEC: u32 = 600;
+const SERVICE_INACTIVITY_TIMEOUT_MS: u32 = 10_000;
+const DEFAULT_AUTOTYPE_DELAY_SEC: u32 = 3;
+const MAX_AUTOTYPE_DELAY_SEC: u32 = 30;
 const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
 const UPPER: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
 const DIGITS: &[u8] = b"0123456789";
 const SPECIAL: &[u8] = b"!@#$%^&*";
-
-static COLOR_SCHEME_INIT: Once = Once::new();
+/// The BIP-39 English word list: 2048 short, unambiguous words, 11 bits each.
+const WORDLIST: &str = include_str!("../data/wordlist.txt");
+/// Accelerators for every action; the shortcuts window shows the first one.
+const SHORTCUTS: &[(&str, &[&str])] = &[
+    ("win.generate", &["<Control>n"]),
+    ("win.copy", &["<Control>c"]),
+    ("win.autotype", &["<Control>k"]),
+    ("win.lowercase", &["<Control>1"]),
+    ("win.uppercase", &["<Control>2"]),
+    ("win.digits", &["<Control>3"]),
+    ("win.special", &["<Control>4"]),
+    ("win.more-groups", &["<Control>plus", "<Control>equal", "<Control>KP_Add"]),
+    ("win.fewer-groups", &["<Control>minus", "<Control>KP_Subtract"]),
+    ("win.auto-close", &["<Control>t"]),
+    ("win.preferences", &["<Control>comma"]),
+    ("win.show-help-overlay", &["<Control>question"]),
+    ("win.close", &["<Control>w"]),
+    ("app.quit", &["<Control>q"]),
+];
 
 fn bool_true() -> bool {
     true
 }
 
+fn default_autotype_delay() -> u32 {
+    DEFAULT_AUTOTYPE_DELAY_SEC
+}
+
+fn default_auto_close_sec() -> i32 {
+    DEFAULT_AUTO_CLOSE_SEC
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct AppSettings {
     groups: i32,
@@ -41,6 +109,37 @@ struct AppSettings {
     allow_special: bool,
     #[serde(default)]
     default_strategy: bool,
+    #[serde(default)]
+    breach_database: Option<PathBuf>,
+    #[serde(default)]
+    kdbx_database: Option<PathBuf>,
+    #[serde(default)]
+    kdbx_key_file: Option<PathBuf>,
+    #[serde(default = "default_autotype_delay")]
+    autotype_delay_sec: u32,
+    /// Lets auto-type skip the RemoteDesktop permission dialog next time.
+    #[serde(default)]
+    autotype_restore_token: Option<String>,
+    #[serde(default = "bool_true")]
+    colorize_password: bool,
+    #[serde(default = "default_auto_close_sec")]
+    auto_close_sec: i32,
+    /// Hold the auto-close countdown while the pointer is over the window or it
+    /// has focus, instead of cancelling it on the first click.
+    #[serde(default)]
+    auto_close_pause: bool,
+    #[serde(default)]
+    close_after_copy: bool,
+    #[serde(default)]
+    hide_on_auto_close: bool,
+    #[serde(default = "bool_true")]
+    notifications: bool,
+    /// Zero keeps the password in the clipboard.
+    #[serde(default)]
+    clear_clipboard_sec: u32,
+    /// A code from `i18n::LANGUAGES`; `None` follows the desktop.
+    #[serde(default)]
+    language: Option<String>,
 }
 
 impl Default for AppSettings {
@@ -54,11 +153,24 @@ impl Default for AppSettings {
             allow_digits: true,
             allow_special: true,
             default_strategy: false,
+            breach_database: None,
+            kdbx_database: None,
+            kdbx_key_file: None,
+            autotype_delay_sec: DEFAULT_AUTOTYPE_DELAY_SEC,
+            autotype_restore_token: None,
+            colorize_password: true,
+            auto_close_sec: DEFAULT_AUTO_CLOSE_SEC,
+            auto_close_pause: false,
+            close_after_copy: false,
+            hide_on_auto_close: false,
+            notifications: true,
+            clear_clipboard_sec: 0,
+            language: None,
         }
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 struct GenerationOptions {
     lowercase: bool,
     uppercase: bool,
@@ -98,10 +210,10 @@ impl GenerationOptions {
     }
 }
 
-type StringsFactory = fn() -> I18nStrings;
-
 #[derive(Clone)]
 struct I18nStrings {
+    /// The language the strings are in, for filling in the templates.
+    language: String,
     app_title: &'static str,
     groups_tooltip: &'static str,
     generate_button: &'static str,
@@ -117,245 +229,722 @@ struct I18nStrings {
     digits_label: &'static str,
     special_label: &'static str,
     clipboard_log_template: &'static str,
+    breach_warning_label: &'static str,
+    breach_source_button: &'static str,
+    analyze_toggle_label: &'static str,
+    analyze_placeholder: &'static str,
+    strength_template: &'static str,
+    crack_instant_label: &'static str,
+    crack_seconds_template: &'static str,
+    crack_minutes_template: &'static str,
+    crack_hours_template: &'static str,
+    crack_days_template: &'static str,
+    crack_years_template: &'static str,
+    crack_centuries_label: &'static str,
+    feedback_common_word: &'static str,
+    feedback_l33t: &'static str,
+    feedback_keyboard_walk: &'static str,
+    feedback_sequence: &'static str,
+    feedback_date: &'static str,
+    feedback_repeat: &'static str,
+    feedback_too_short: &'static str,
+    keyring_button: &'static str,
+    keyring_label_field: &'static str,
+    keyring_username_field: &'static str,
+    keyring_url_field: &'static str,
+    save_button: &'static str,
+    cancel_button: &'static str,
+    keyring_saved_label: &'static str,
+    keyring_error_template: &'static str,
+    pass_button: &'static str,
+    pass_folder_field: &'static str,
+    pass_root_folder: &'static str,
+    pass_name_field: &'static str,
+    pass_overwrite_label: &'static str,
+    pass_saved_label: &'static str,
+    pass_error_template: &'static str,
+    kdbx_button: &'static str,
+    kdbx_database_field: &'static str,
+    kdbx_key_file_field: &'static str,
+    kdbx_master_password_field: &'static str,
+    kdbx_title_field: &'static str,
+    kdbx_notes_field: &'static str,
+    kdbx_choose_file: &'static str,
+    kdbx_saved_label: &'static str,
+    bulk_button: &'static str,
+    bulk_count_field: &'static str,
+    bulk_usernames_field: &'static str,
+    bulk_format_field: &'static str,
+    bulk_warning_label: &'static str,
+    export_button: &'static str,
+    bulk_exported_template: &'static str,
+    search_result_description: &'static str,
+    shortcuts_title: &'static str,
+    shortcuts_password_group: &'static str,
+    shortcuts_general_group: &'static str,
+    shortcut_generate: &'static str,
+    shortcut_copy: &'static str,
+    shortcut_more_groups: &'static str,
+    shortcut_fewer_groups: &'static str,
+    shortcut_show_shortcuts: &'static str,
+    shortcut_close: &'static str,
+    shortcut_quit: &'static str,
+    global_shortcut_description: &'static str,
+    global_shortcut_notification: &'static str,
+    autotype_button: &'static str,
+    autotype_delay_tooltip: &'static str,
+    autotype_done_label: &'static str,
+    autotype_error_template: &'static str,
+    shortcut_autotype: &'static str,
+    qr_toggle_label: &'static str,
+    qr_wifi_label: &'static str,
+    qr_ssid_placeholder: &'static str,
+    spell_out_label: &'static str,
+    colorize_label: &'static str,
+    preferences_title: &'static str,
+    preferences_behavior_group: &'static str,
+    preferences_breach_group: &'static str,
+    main_menu_tooltip: &'static str,
+    autotype_delay_label: &'static str,
+    auto_close_duration_label: &'static str,
+    auto_close_pause_label: &'static str,
+    close_after_copy_label: &'static str,
+    hide_on_auto_close_label: &'static str,
+    notifications_label: &'static str,
+    clear_clipboard_label: &'static str,
+    clear_clipboard_subtitle: &'static str,
+    notification_cleared_body: &'static str,
+    notification_copy_again: &'static str,
+    notification_generate_new: &'static str,
+    language_label: &'static str,
+    language_system_label: &'static str,
+    /// Spelling alphabet words for A to Z, written in lowercase.
+    phonetic_letters: [&'static str; 26],
+    phonetic_digits: [&'static str; 10],
+    /// Names of the `SPECIAL` characters, in the same order.
+    phonetic_symbols: [&'static str; 8],
 }
 
 impl I18nStrings {
+    /// Looks up every text in `catalog`. The lookups go through plain
+    /// `gettext`/`pgettext` calls so `xgettext` finds the messages.
+    fn new(catalog: &impl Catalog) -> Self {
+        let gettext = |msgid: &str| catalog.gettext(msgid);
+        let pgettext = |context: &str, msgid: &str| catalog.pgettext(context, msgid);
+        Self {
+            language: catalog.language(),
+            app_title: gettext("Password Generator"),
+            groups_tooltip: gettext("Number of groups (5 chars each)"),
+            generate_button: gettext("New"),
+            copy_button: gettext("Copy"),
+            auto_close_label: gettext("Auto-Close"),
+            copy_immediately_label: gettext("Copy immediately"),
+            default_strategy_label: gettext("Default strategy"),
+            // Translators: {seconds} is the number of seconds until the window closes.
+            // Give the forms for every plural category of your language, see
+            // https://www.unicode.org/cldr/charts/latest/supplemental/language_plural_rules.html
+            timer_template: gettext("{seconds, plural, one {Closes in # second} other {Closes in # seconds}}"),
+            copy_success_label: gettext("Copied"),
+            charset_section_label: gettext("Character sets"),
+            lowercase_label: gettext("Lowercase"),
+            uppercase_label: gettext("Uppercase"),
+            digits_label: gettext("Digits"),
+            special_label: gettext("Special"),
+            // Translators: Written to the log; {length} is the number of characters
+            clipboard_log_template: gettext("Copied to clipboard: {length, plural, one {# character} other {# characters}}"),
+            breach_warning_label: gettext("Found in known data breaches"),
+            breach_source_button: gettext("Breach data…"),
+            analyze_toggle_label: gettext("Analyze"),
+            analyze_placeholder: gettext("Paste a password to analyze"),
+            // Translators: {bits} is a number, {time} one of the crack times below
+            strength_template: gettext("{bits, plural, one {# bit} other {# bits}} of entropy, cracked in {time}"),
+            crack_instant_label: gettext("less than a second"),
+            // Translators: {count} is a number
+            crack_seconds_template: gettext("{count, plural, one {# second} other {# seconds}}"),
+            crack_minutes_template: gettext("{count, plural, one {# minute} other {# minutes}}"),
+            crack_hours_template: gettext("{count, plural, one {# hour} other {# hours}}"),
+            crack_days_template: gettext("{count, plural, one {# day} other {# days}}"),
+            crack_years_template: gettext("{count, plural, one {# year} other {# years}}"),
+            crack_centuries_label: gettext("centuries"),
+            feedback_common_word: gettext("Contains a common word or password"),
+            feedback_l33t: gettext("Swaps like @ for a are easy to guess"),
+            feedback_keyboard_walk: gettext("Contains a keyboard pattern"),
+            feedback_sequence: gettext("Contains a sequence like abc or 123"),
+            feedback_date: gettext("Contains a date or year"),
+            feedback_repeat: gettext("Contains repeated characters or blocks"),
+            feedback_too_short: gettext("Use at least 12 characters"),
+            keyring_button: gettext("Save to keyring"),
+            keyring_label_field: gettext("Label"),
+            keyring_username_field: gettext("Username"),
+            keyring_url_field: gettext("URL"),
+            save_button: gettext("Save"),
+            cancel_button: gettext("Cancel"),
+            keyring_saved_label: gettext("Saved to keyring"),
+            // Translators: {error} is the error message
+            keyring_error_template: gettext("Could not save: {error}"),
+            pass_button: gettext("Insert into pass"),
+            pass_folder_field: gettext("Folder"),
+            pass_root_folder: gettext("(top level)"),
+            pass_name_field: gettext("Name"),
+            pass_overwrite_label: gettext("Overwrite existing entry"),
+            pass_saved_label: gettext("Inserted into pass"),
+            // Translators: {error} is the error message
+            pass_error_template: gettext("Could not insert into pass: {error}"),
+            kdbx_button: gettext("Add to KeePass"),
+            kdbx_database_field: gettext("Database"),
+            kdbx_key_file_field: gettext("Key file"),
+            kdbx_master_password_field: gettext("Master password"),
+            kdbx_title_field: gettext("Title"),
+            kdbx_notes_field: gettext("Notes"),
+            kdbx_choose_file: gettext("Choose…"),
+            kdbx_saved_label: gettext("Added to KeePass database"),
+            bulk_button: gettext("Bulk…"),
+            bulk_count_field: gettext("Count"),
+            bulk_usernames_field: gettext("Usernames (optional, one per line)"),
+            bulk_format_field: gettext("Format"),
+            bulk_warning_label: gettext("The exported file contains every password in plain text. Keep it safe and delete it after importing."),
+            export_button: gettext("Export…"),
+            // Translators: {count} is the number of passwords
+            bulk_exported_template: gettext("{count, plural, one {Exported # password} other {Exported # passwords}}"),
+            search_result_description: gettext("Click to copy"),
+            shortcuts_title: gettext("Keyboard Shortcuts"),
+            shortcuts_password_group: gettext("Password"),
+            shortcuts_general_group: gettext("General"),
+            shortcut_generate: gettext("Generate a new password"),
+            shortcut_copy: gettext("Copy the password"),
+            shortcut_more_groups: gettext("More groups"),
+            shortcut_fewer_groups: gettext("Fewer groups"),
+            shortcut_show_shortcuts: gettext("Show keyboard shortcuts"),
+            shortcut_close: gettext("Close the window"),
+            shortcut_quit: gettext("Quit"),
+            global_shortcut_description: gettext("Generate and copy a new password"),
+            global_shortcut_notification: gettext("A new password was copied to the clipboard"),
+            autotype_button: gettext("Auto-type"),
+            autotype_delay_tooltip: gettext("Seconds to wait before typing, to focus the target window"),
+            autotype_done_label: gettext("Typed"),
+            // Translators: {error} is the error message
+            autotype_error_template: gettext("Could not type the password: {error}"),
+            shortcut_autotype: gettext("Type the password into the previous window"),
+            qr_toggle_label: gettext("QR code"),
+            qr_wifi_label: gettext("Wi-Fi network"),
+            qr_ssid_placeholder: gettext("Network name (SSID)"),
+            spell_out_label: gettext("Spell out"),
+            colorize_label: gettext("Color character classes"),
+            preferences_title: gettext("Preferences"),
+            preferences_behavior_group: gettext("Behavior"),
+            preferences_breach_group: gettext("Breach Check"),
+            main_menu_tooltip: gettext("Main Menu"),
+            autotype_delay_label: gettext("Auto-type delay"),
+            auto_close_duration_label: gettext("Auto-close after (seconds)"),
+            auto_close_pause_label: gettext("Pause while hovered or focused"),
+            close_after_copy_label: gettext("Close after copying"),
+            hide_on_auto_close_label: gettext("Keep running in the background"),
+            notifications_label: gettext("Desktop notifications"),
+            clear_clipboard_label: gettext("Clear clipboard after (seconds)"),
+            clear_clipboard_subtitle: gettext("0 keeps the password in the clipboard"),
+            notification_cleared_body: gettext("The password was cleared from the clipboard"),
+            notification_copy_again: gettext("Copy Again"),
+            notification_generate_new: gettext("Generate New"),
+            language_label: gettext("Language"),
+            language_system_label: gettext("System language"),
+            phonetic_letters: [
+                // Translators: The words of your spelling alphabet for A to Z, in lowercase
+                pgettext("spelling alphabet", "alfa"),
+                pgettext("spelling alphabet", "bravo"),
+                pgettext("spelling alphabet", "charlie"),
+                pgettext("spelling alphabet", "delta"),
+                pgettext("spelling alphabet", "echo"),
+                pgettext("spelling alphabet", "foxtrot"),
+                pgettext("spelling alphabet", "golf"),
+                pgettext("spelling alphabet", "hotel"),
+                pgettext("spelling alphabet", "india"),
+                pgettext("spelling alphabet", "juliett"),
+                pgettext("spelling alphabet", "kilo"),
+                pgettext("spelling alphabet", "lima"),
+                pgettext("spelling alphabet", "mike"),
+                pgettext("spelling alphabet", "november"),
+                pgettext("spelling alphabet", "oscar"),
+                pgettext("spelling alphabet", "papa"),
+                pgettext("spelling alphabet", "quebec"),
+                pgettext("spelling alphabet", "romeo"),
+                pgettext("spelling alphabet", "sierra"),
+                pgettext("spelling alphabet", "tango"),
+                pgettext("spelling alphabet", "uniform"),
+                pgettext("spelling alphabet", "victor"),
+                pgettext("spelling alphabet", "whiskey"),
+                pgettext("spelling alphabet", "x-ray"),
+                pgettext("spelling alphabet", "yankee"),
+                pgettext("spelling alphabet", "zulu"),
+            ],
+            phonetic_digits: [
+                // Translators: How the digits 0 to 9 are read out when spelling
+                pgettext("spelling alphabet", "zero"),
+                pgettext("spelling alphabet", "one"),
+                pgettext("spelling alphabet", "two"),
+                pgettext("spelling alphabet", "three"),
+                pgettext("spelling alphabet", "four"),
+                pgettext("spelling alphabet", "five"),
+                pgettext("spelling alphabet", "six"),
+                pgettext("spelling alphabet", "seven"),
+                pgettext("spelling alphabet", "eight"),
+                pgettext("spelling alphabet", "nine"),
+            ],
+            phonetic_symbols: [
+                // Translators: Names of the characters ! @ # $ % ^ & *, in this order
+                pgettext("spelling alphabet", "exclamation mark"),
+                pgettext("spelling alphabet", "at sign"),
+                pgettext("spelling alphabet", "hash"),
+                pgettext("spelling alphabet", "dollar"),
+                pgettext("spelling alphabet", "percent"),
+                pgettext("spelling alphabet", "caret"),
+                pgettext("spelling alphabet", "ampersand"),
+                pgettext("spelling alphabet", "asterisk"),
+            ],
+        }
+    }
+
+    /// Fills in `template` with the plural rules and number format of the
+    /// language.
+    fn format(&self, template: &str, args: &[(&str, Arg)]) -> String {
+        template::format(&self.language, template, args)
+    }
+
     fn timer_label(&self, seconds: i32) -> String {
-        self.timer_template
-            .replace("{seconds}", &seconds.to_string())
+        self.format(self.timer_template, &[("seconds", Arg::Number(seconds.max(0) as u64))])
     }
 
-    fn clipboard_log(&self, password: &str) -> String {
-        let first = password.chars().next().unwrap_or('?');
-        let length = password.chars().count();
-        self.clipboard_log_template
-            .replace("{first}", &first.to_string())
-            .replace("{length}", &length.to_string())
+    fn strength_label(&self, bits: f64) -> String {
+        let count = |template: &str, count: u64| self.format(template, &[("count", Arg::Number(count))]);
+        let time = match strength::crack_time(bits) {
+            CrackTime::Instant => self.crack_instant_label.to_string(),
+            CrackTime::Seconds(n) => count(self.crack_seconds_template, n),
+            CrackTime::Minutes(n) => count(self.crack_minutes_template, n),
+            CrackTime::Hours(n) => count(self.crack_hours_template, n),
+            CrackTime::Days(n) => count(self.crack_days_template, n),
+            CrackTime::Years(n) => count(self.crack_years_template, n),
+            CrackTime::Centuries => self.crack_centuries_label.to_string(),
+        };
+        self.format(
+            self.strength_template,
+            &[("bits", Arg::Number(bits.round() as u64)), ("time", Arg::Text(&time))],
+        )
     }
-}
 
-fn localized_strings() -> I18nStrings {
-    for lang in glib::language_names() {
-        if let Some(strings) = strings_for_code(lang.as_str()) {
-            return strings;
+    fn feedback_label(&self, feedback: Feedback) -> &'static str {
+        match feedback {
+            Feedback::CommonWord => self.feedback_common_word,
+            Feedback::L33tSubstitution => self.feedback_l33t,
+            Feedback::KeyboardWalk => self.feedback_keyboard_walk,
+            Feedback::Sequence => self.feedback_sequence,
+            Feedback::Date => self.feedback_date,
+            Feedback::Repeat => self.feedback_repeat,
+            Feedback::TooShort => self.feedback_too_short,
         }
     }
-    strings_en()
-}
 
-fn strings_for_code(language: &str) -> Option<I18nStrings> {
-    let short = language
-        .split(|c| c == '-' || c == '_')
-        .next()
-        .unwrap_or(language);
-    AVAILABLE_TRANSLATIONS
-        .iter()
-        .find(|(code, _)| *code == short)
-        .map(|(_, factory)| factory())
-}
+    /// Spells `password` with the spelling alphabet, one line per group.
+    /// Letter case shows in the case of the word, e.g. "QUEBEC" for `Q`.
+    fn spelled_out(&self, password: &str) -> String {
+        password
+            .split('-')
+            .map(|group| {
+                group
+                    .chars()
+                    .map(|c| {
+                        let index = |base: u8| (c as u8 - base) as usize;
+                        match c {
+                            'a'..='z' => self.phonetic_letters[index(b'a')].to_lowercase(),
+                            'A'..='Z' => self.phonetic_letters[index(b'A')].to_uppercase(),
+                            '0'..='9' => self.phonetic_digits[index(b'0')].to_string(),
+                            _ => match SPECIAL.iter().position(|&special| char::from(special) == c) {
+                                Some(position) => self.phonetic_symbols[position].to_string(),
+                                None => c.to_string(),
+                            },
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 
-const AVAILABLE_TRANSLATIONS: &[(&str, StringsFactory)] = &[
-    ("de", strings_de),
-    ("ja", strings_ja),
-    ("sv", strings_sv),
-    ("es", strings_es),
-    ("it", strings_it),
-    ("fr", strings_fr),
-    ("en", strings_en),
-];
+    fn clipboard_log(&self, length: usize) -> String {
+        self.format(self.clipboard_log_template, &[("length", Arg::Number(length as u64))])
+    }
 
-fn strings_en() -> I18nStrings {
-    I18nStrings {
-        app_title: "Password Generator",
-        groups_tooltip: "Number of groups (5 chars each)",
-        generate_button: "New",
-        copy_button: "Copy",
-        auto_close_label: "Auto-Close",
-        copy_immediately_label: "Copy immediately",
-        default_strategy_label: "Default strategy",
-        timer_template: "Closes in {seconds}s",
-        copy_success_label: "Copied",
-        charset_section_label: "Character sets",
-        lowercase_label: "Lowercase",
-        uppercase_label: "Uppercase",
-        digits_label: "Digits",
-        special_label: "Special",
-        clipboard_log_template: "Copied to clipboard: first '{first}', length {length}",
+    fn save_error_label(&self, error: &str) -> String {
+        self.format(self.keyring_error_template, &[("error", Arg::Text(error))])
     }
-}
 
-fn strings_de() -> I18nStrings {
-    I18nStrings {
-        app_title: "Passwortgenerator",
-        groups_tooltip: "Anzahl Gruppen (je 5 Zeichen)",
-        generate_button: "Neu",
-        copy_button: "Kopieren",
-        auto_close_label: "Auto-Schließen",
-        copy_immediately_label: "Sofort kopieren",
-        default_strategy_label: "Standardstrategie",
-        timer_template: "Schließt in {seconds}s",
-        copy_success_label: "Kopiert",
-        charset_section_label: "Zeichensätze",
-        lowercase_label: "Kleinbuchstaben",
-        uppercase_label: "Großbuchstaben",
-        digits_label: "Ziffern",
-        special_label: "Sonderzeichen",
-        clipboard_log_template: "In Zwischenablage kopiert: erster Buchstabe '{first}', Länge {length}",
+    fn pass_error_label(&self, error: &str) -> String {
+        self.format(self.pass_error_template, &[("error", Arg::Text(error))])
     }
-}
 
-fn strings_ja() -> I18nStrings {
-    I18nStrings {
-        app_title: "パスワードジェネレーター",
-        groups_tooltip: "グループ数 (5 文字ごと)",
-        generate_button: "新規",
-        copy_button: "コピー",
-        auto_close_label: "自動終了",
-        copy_immediately_label: "すぐにコピー",
-        default_strategy_label: "デフォルト戦略",
-        timer_template: "あと {seconds} 秒で閉じます",
-        copy_success_label: "コピーしました",
-        charset_section_label: "文字セット",
-        lowercase_label: "小文字",
-        uppercase_label: "大文字",
-        digits_label: "数字",
-        special_label: "記号",
-        clipboard_log_template: "クリップボードにコピー: 先頭 '{first}', 長さ {length}",
+    fn autotype_error_label(&self, error: &str) -> String {
+        self.format(self.autotype_error_template, &[("error", Arg::Text(error))])
     }
-}
 
-fn strings_sv() -> I18nStrings {
-    I18nStrings {
-        app_title: "Lösenordsgenerator",
-        groups_tooltip: "Antal grupper (5 tecken vardera)",
-        generate_button: "Nytt",
-        copy_button: "Kopiera",
-        auto_close_label: "Stäng automatiskt",
-        copy_immediately_label: "Kopiera direkt",
-        default_strategy_label: "Standardstrategi",
-        timer_template: "Stänger om {seconds}s",
-        copy_success_label: "Kopierat",
-        charset_section_label: "Teckenuppsättningar",
-        lowercase_label: "Gemener",
-        uppercase_label: "Versaler",
-        digits_label: "Siffror",
-        special_label: "Specialtecken",
-        clipboard_log_template: "Kopierat till urklipp: första '{first}', längd {length}",
+    fn bulk_exported_label(&self, count: usize) -> String {
+        self.format(self.bulk_exported_template, &[("count", Arg::Number(count as u64))])
     }
 }
 
-fn strings_es() -> I18nStrings {
-    I18nStrings {
-        app_title: "Generador de contraseñas",
-        groups_tooltip: "Número de grupos (5 caracteres cada uno)",
-        generate_button: "Nuevo",
-        copy_button: "Copiar",
-        auto_close_label: "Cierre automático",
-        copy_immediately_label: "Copiar al instante",
-        default_strategy_label: "Estrategia predeterminada",
-        timer_template: "Se cierra en {seconds}s",
-        copy_success_label: "Copiado",
-        charset_section_label: "Conjuntos de caracteres",
-        lowercase_label: "Minúsculas",
-        uppercase_label: "Mayúsculas",
-        digits_label: "Dígitos",
-        special_label: "Caracteres especiales",
-        clipboard_log_template: "Copiado al portapapeles: primera '{first}', longitud {length}",
-    }
+fn localized_strings() -> I18nStrings {
+    I18nStrings::new(&i18n::Gettext)
 }
 
-fn strings_it() -> I18nStrings {
-    I18nStrings {
-        app_title: "Generatore di password",
-        groups_tooltip: "Numero di gruppi (5 caratteri ciascuno)",
-        generate_button: "Nuovo",
-        copy_button: "Copia",
-        auto_close_label: "Chiusura automatica",
-        copy_immediately_label: "Copia immediata",
-        default_strategy_label: "Strategia predefinita",
-        timer_template: "Si chiude tra {seconds}s",
-        copy_success_label: "Copiato",
-        charset_section_label: "Set di caratteri",
-        lowercase_label: "Minuscole",
-        uppercase_label: "Maiuscole",
-        digits_label: "Numeri",
-        special_label: "Caratteri speciali",
-        clipboard_log_template: "Copiato negli appunti: prima '{first}', lunghezza {length}",
-    }
+/// The strings of the main window, kept current when the language changes
+/// while it is open. Widgets get their texts through [`LiveStrings::bind`].
+struct LiveStrings {
+    current: RefCell<I18nStrings>,
+    bindings: RefCell<Vec<Box<dyn Fn(&I18nStrings)>>>,
 }
 
-fn strings_fr() -> I18nStrings {
-    I18nStrings {
-        app_title: "Générateur de mots de passe",
-        groups_tooltip: "Nombre de groupes (5 caractères chacun)",
-        generate_button: "Nouveau",
-        copy_button: "Copier",
-        auto_close_label: "Fermeture auto",
-        copy_immediately_label: "Copier immédiatement",
-        default_strategy_label: "Stratégie par défaut",
-        timer_template: "Fermeture dans {seconds}s",
-        copy_success_label: "Copié",
-        charset_section_label: "Jeux de caractères",
-        lowercase_label: "Minuscules",
-        uppercase_label: "Majuscules",
-        digits_label: "Chiffres",
-        special_label: "Caractères spéciaux",
-        clipboard_log_template: "Copié dans le presse-papiers : première '{first}', longueur {length}",
+impl LiveStrings {
+    fn new() -> Rc<Self> {
+        Rc::new(Self {
+            current: RefCell::new(localized_strings()),
+            bindings: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn current(&self) -> std::cell::Ref<'_, I18nStrings> {
+        self.current.borrow()
+    }
+
+    /// Sets the texts of `widget` now and again after every language change,
+    /// for as long as the widget exists.
+    fn bind<W: IsA<glib::Object>>(&self, widget: &W, apply: impl Fn(&W, &I18nStrings) + 'static) {
+        apply(widget, &self.current());
+        let widget_weak = widget.downgrade();
+        self.bindings.borrow_mut().push(Box::new(move |strings| {
+            if let Some(widget) = widget_weak.upgrade() {
+                apply(&widget, strings);
+            }
+        }));
+    }
+
+    /// Looks every string up again after `i18n::set_language` and relabels
+    /// the bound widgets.
+    fn reload(&self) {
+        self.current.replace(localized_strings());
+        let strings = self.current();
+        for binding in self.bindings.borrow().iter() {
+            binding(&strings);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use i18n::po::{self, PoCatalog};
+
+    /// English comes from the template, every other language from its catalog.
+    fn catalogs() -> Vec<(String, PoCatalog)> {
+        let mut catalogs = vec![("en".to_string(), PoCatalog::template())];
+        catalogs.extend(po::languages().into_iter().map(|language| {
+            let catalog = PoCatalog::load(&language);
+            (language, catalog)
+        }));
+        catalogs
+    }
 
     #[test]
     fn translations_cover_all_languages() {
-        for (code, factory) in AVAILABLE_TRANSLATIONS {
-            let strings = factory();
+        for (language, catalog) in catalogs() {
+            I18nStrings::new(&catalog);
             assert!(
-                !strings.app_title.is_empty()
-                    && !strings.generate_button.is_empty()
-                    && !strings.copy_button.is_empty()
-                    && !strings.default_strategy_label.is_empty(),
-                "Missing strings for language code {}",
-                code
+                catalog.missing().is_empty(),
+                "Missing strings for language code {}: {:?}",
+                language,
+                catalog.missing()
             );
         }
     }
-}
 
-fn ensure_system_color_scheme() {
-    COLOR_SCHEME_INIT.call_once(|| {
-        if let Some(gtk_settings) = gtk::Settings::default() {
-            if has_gsettings_schema("org.gnome.desktop.interface") {
-                let interface_settings = Settings::new("org.gnome.desktop.interface");
-                apply_system_color_preference(&gtk_settings, &interface_settings);
-                let gtk_settings_for_change = gtk_settings.clone();
-                interface_settings.connect_changed(Some("color-scheme"), move |settings, key| {
-                    if key == "color-scheme" {
-                        apply_system_color_preference(&gtk_settings_for_change, settings);
-                    }
-                });
-                unsafe {
-                    gtk_settings.set_data("system-color-settings", interface_settings);
+    const SAMPLES: usize = 4000;
+
+    /// Upper bound for a chi-squared statistic with `df` degrees of freedom at
+    /// p = 0.0001 (Wilson-Hilferty approximation), so random failures stay rare.
+    fn chi_squared_limit(df: usize) -> f64 {
+        let df = df as f64;
+        let z = 3.719;
+        let term = 2.0 / (9.0 * df);
+        df * (1.0 - term + z * term.sqrt()).powi(3)
+    }
+
+    fn assert_uniform(counts: &[usize], what: &str) {
+        let total: usize = counts.iter().sum();
+        let expected = total as f64 / counts.len() as f64;
+        let statistic: f64 = counts
+            .iter()
+            .map(|&observed| {
+                let diff = observed as f64 - expected;
+                diff * diff / expected
+            })
+            .sum();
+        let limit = chi_squared_limit(counts.len() - 1);
+        assert!(
+            statistic < limit,
+            "{} is not uniform: chi-squared {:.2} >= {:.2} ({:?})",
+            what,
+            statistic,
+            limit,
+            counts
+        );
+    }
+
+    fn pool_counts(pool: &[u8], chars: impl Iterator<Item = u8>) -> Vec<usize> {
+        let mut counts = vec![0usize; pool.len()];
+        for c in chars {
+            let idx = pool
+                .iter()
+                .position(|&p| p == c)
+                .unwrap_or_else(|| panic!("character {:?} outside of pool", c as char));
+            counts[idx] += 1;
+        }
+        counts
+    }
+
+    fn all_options() -> Vec<GenerationOptions> {
+        (1..16u8)
+            .map(|bits| {
+                GenerationOptions::new(bits & 1 != 0, bits & 2 != 0, bits & 4 != 0, bits & 8 != 0)
+            })
+            .collect()
+    }
+
+    fn strip_groups(password: SecretString) -> Vec<u8> {
+        password.as_str().bytes().filter(|&b| b != b'-').collect()
+    }
+
+    #[test]
+    fn templates_keep_their_placeholders() {
+        for (code, catalog) in catalogs() {
+            let strings = I18nStrings::new(&catalog);
+            assert_eq!(template::arguments(strings.timer_template), ["seconds"], "{}", code);
+            assert_eq!(template::arguments(strings.clipboard_log_template), ["length"], "{}", code);
+            assert_eq!(template::arguments(strings.strength_template), ["bits", "time"], "{}", code);
+            assert_eq!(template::arguments(strings.keyring_error_template), ["error"], "{}", code);
+            assert_eq!(template::arguments(strings.pass_error_template), ["error"], "{}", code);
+            assert_eq!(template::arguments(strings.autotype_error_template), ["error"], "{}", code);
+            assert_eq!(template::arguments(strings.bulk_exported_template), ["count"], "{}", code);
+            for template in [
+                strings.crack_seconds_template,
+                strings.crack_minutes_template,
+                strings.crack_hours_template,
+                strings.crack_days_template,
+                strings.crack_years_template,
+            ] {
+                assert_eq!(template::arguments(template), ["count"], "{}: {}", code, template);
+            }
+        }
+    }
+
+    #[test]
+    fn templates_fill_in_every_placeholder() {
+        for (code, catalog) in catalogs() {
+            let strings = I18nStrings::new(&catalog);
+            for n in [0, 1, 2, 5, 21, 1000, 1_000_000] {
+                for text in [
+                    strings.timer_label(n as i32),
+                    strings.clipboard_log(n),
+                    strings.strength_label(n as f64),
+                    strings.bulk_exported_label(n),
+                    strings.save_error_label("denied"),
+                    strings.pass_error_label("denied"),
+                    strings.autotype_error_label("denied"),
+                ] {
+                    assert!(!text.contains(['{', '}', '#']), "{} {}: {}", code, n, text);
                 }
             }
         }
-    });
-}
+    }
 
-fn has_gsettings_schema(id: &str) -> bool {
-    SettingsSchemaSource::default()
-        .and_then(|src| src.lookup(id, true))
-        .is_some()
-}
+    #[test]
+    fn templates_use_plural_forms_and_number_format_per_language() {
+        let expected = [
+            ("en", "Closes in 1 second", "Closes in 2 seconds", "Exported 1,000 passwords"),
+            ("de", "Schließt in 1 Sekunde", "Schließt in 2 Sekunden", "1.000 Passwörter exportiert"),
+            ("es", "Se cierra en 1 segundo", "Se cierra en 2 segundos", "Se exportaron 1000 contraseñas"),
+            ("fr", "Fermeture dans 1 seconde", "Fermeture dans 2 secondes", "1\u{202f}000 mots de passe exportés"),
+            ("it", "Si chiude tra 1 secondo", "Si chiude tra 2 secondi", "1.000 password esportate"),
+            ("ja", "あと 1 秒で閉じます", "あと 2 秒で閉じます", "1,000 件のパスワードをエクスポートしました"),
+            ("sv", "Stänger om 1 sekund", "Stänger om 2 sekunder", "Exporterade 1\u{a0}000 lösenord"),
+        ];
+        let catalogs = catalogs();
+        assert_eq!(catalogs.len(), expected.len());
+        for (code, one, two, exported) in expected {
+            let (_, catalog) = catalogs.iter().find(|(language, _)| language == code).unwrap();
+            let strings = I18nStrings::new(catalog);
+            assert_eq!(strings.timer_label(1), one);
+            assert_eq!(strings.timer_label(2), two);
+            assert_eq!(strings.bulk_exported_label(1000), exported);
+        }
+
+        let french = I18nStrings::new(&PoCatalog::load("fr"));
+        assert_eq!(french.timer_label(0), "Fermeture dans 0 seconde");
+        assert_eq!(french.bulk_exported_label(1_000_000), "1\u{202f}000\u{202f}000 de mots de passe exportés");
+        let german = I18nStrings::new(&PoCatalog::load("de"));
+        assert_eq!(german.clipboard_log(1), "In Zwischenablage kopiert: 1 Zeichen");
+        assert_eq!(german.strength_label(1.0), "1 Bit Entropie, geknackt in weniger als einer Sekunde");
+    }
+
+    #[test]
+    fn entropy_matches_the_size_of_the_output_space() {
+        let all = GenerationOptions::new(true, true, true, true);
+        let expected = 15.0 * 70f64.log2();
+        assert!((password_entropy_bits(3, &all, false) - expected).abs() < 1e-9);
+
+        // 15 * 14 * 13 placements, 26 * 10 * 8 forced characters, 12 lowercase characters
+        let expected = (15.0 * 14.0 * 13.0 * 26.0 * 10.0 * 8.0f64).log2() + 12.0 * 26f64.log2();
+        assert!((password_entropy_bits(3, &all, true) - expected).abs() < 1e-9);
+
+        let lower_only = GenerationOptions::new(true, false, false, false);
+        let expected = 5.0 * 26f64.log2();
+        assert!((password_entropy_bits(1, &lower_only, true) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn passwords_are_laid_out_in_groups_of_five() {
+        let options = GenerationOptions::new(true, true, true, true);
+        for strategy in [false, true] {
+            for groups in 1..=10 {
+                let password = generate_password(groups, &options, strategy);
+                let password = password.as_str();
+                let parts: Vec<&str> = password.split('-').collect();
+                assert_eq!(parts.len(), groups as usize, "{}", password);
+                assert!(parts.iter().all(|part| part.len() == 5), "{}", password);
+            }
+            let clamped = generate_password(0, &options, strategy);
+            assert_eq!(clamped.as_str().len(), 5, "{}", clamped.as_str());
+        }
+    }
+
+    #[test]
+    fn pins_and_passphrases_use_their_alphabets() {
+        let pin = generate_pin(8);
+        assert_eq!(pin.as_str().len(), 8);
+        assert!(pin.as_str().bytes().all(|c| c.is_ascii_digit()));
+
+        let words: Vec<&str> = WORDLIST.lines().collect();
+        assert_eq!(words.len(), 2048);
+        assert_eq!(words.iter().collect::<std::collections::HashSet<_>>().len(), 2048);
+        let passphrase = generate_passphrase(5);
+        let parts: Vec<&str> = passphrase.as_str().split('-').collect();
+        assert_eq!(parts.len(), 5);
+        assert!(parts.iter().all(|part| words.contains(part)), "{}", passphrase.as_str());
+    }
+
+    #[test]
+    fn custom_strategy_is_uniform_over_the_selected_pool() {
+        for options in all_options() {
+            let pool = options.pool();
+            let chars = (0..SAMPLES / 10)
+                .flat_map(|_| strip_groups(generate_password(10, &options, false)));
+            let counts = pool_counts(&pool, chars);
+            let name = format!("pool {:?}", String::from_utf8_lossy(&pool));
+            assert_uniform(&counts, &name);
+        }
+    }
 
-fn apply_system_color_preference(gtk_settings: &gtk::Settings, interface_settings: &Settings) {
-    let prefer_dark = interface_settings
-        .string("color-scheme")
-        .as_str()
-        .eq_ignore_ascii_case("prefer-dark");
-    gtk_settings.set_gtk_application_prefer_dark_theme(prefer_dark);
+    #[test]
+    fn default_strategy_places_exactly_one_of_each_forced_class() {
+        for options in all_options() {
+            for _ in 0..200 {
+                let password = strip_groups(generate_password(3, &options, true));
+                let count = |pool: &[u8]| password.iter().filter(|c| pool.contains(c)).count();
+                assert_eq!(count(UPPER), options.uppercase as usize);
+                assert_eq!(count(DIGITS), options.digits as usize);
+                assert_eq!(count(SPECIAL), options.special as usize);
+                let forced = options.uppercase as usize
+                    + options.digits as usize
+                    + options.special as usize;
+                assert_eq!(count(LOWER), password.len() - forced);
+            }
+        }
+    }
+
+    #[test]
+    fn default_strategy_is_uniform_within_each_pool() {
+        let options = GenerationOptions::new(true, true, true, true);
+        let passwords: Vec<Vec<u8>> = (0..SAMPLES)
+            .map(|_| strip_groups(generate_password(2, &options, true)))
+            .collect();
+        let pools = [
+            ("lower", LOWER),
+            ("upper", UPPER),
+            ("digits", DIGITS),
+            ("special", SPECIAL),
+        ];
+        for (name, pool) in pools {
+            let chars = passwords
+                .iter()
+                .flat_map(|p| p.iter().copied())
+                .filter(|c| pool.contains(c));
+            assert_uniform(&pool_counts(pool, chars), name);
+        }
+    }
+
+    #[test]
+    fn default_strategy_has_no_positional_bias() {
+        let options = GenerationOptions::new(true, true, true, true);
+        let groups = 2;
+        let length = groups as usize * 5;
+        let mut positions = [vec![0usize; length], vec![0usize; length], vec![0usize; length]];
+        for _ in 0..SAMPLES {
+            let password = strip_groups(generate_password(groups, &options, true));
+            for (class, pool) in [UPPER, DIGITS, SPECIAL].into_iter().enumerate() {
+                let pos = password.iter().position(|c| pool.contains(c)).unwrap();
+                positions[class][pos] += 1;
+            }
+        }
+        for (name, counts) in ["upper", "digits", "special"].iter().zip(positions.iter()) {
+            assert_uniform(counts, &format!("{} positions", name));
+        }
+    }
+
+    #[test]
+    fn spelling_shows_case_and_groups() {
+        let strings = I18nStrings::new(&PoCatalog::template());
+        let german = I18nStrings::new(&PoCatalog::load("de"));
+        assert_eq!(strings.spelled_out("xQ7#k"), "x-ray, QUEBEC, seven, hash, kilo");
+        assert_eq!(strings.spelled_out("aB-9!"), "alfa, BRAVO\nnine, exclamation mark");
+        assert_eq!(german.spelled_out("C"), "CÄSAR");
+        assert_eq!(german.spelled_out("c"), "cäsar");
+    }
+
+    #[test]
+    fn class_runs_merge_neighbours_of_the_same_class() {
+        assert_eq!(
+            class_runs("ab1O-l0#"),
+            vec![
+                (0, 2, CharClass::Lowercase),
+                (2, 3, CharClass::Digit),
+                (3, 4, CharClass::Uppercase),
+                (5, 6, CharClass::Lowercase),
+                (6, 7, CharClass::Digit),
+                (7, 8, CharClass::Special),
+            ]
+        );
+        assert_eq!(class_runs("é1"), vec![(2, 3, CharClass::Digit)]);
+    }
+
+    #[test]
+    fn shortcuts_window_lists_every_accelerator() {
+        let shown: Vec<&str> = shortcut_groups(&I18nStrings::new(&PoCatalog::template()))
+            .iter()
+            .flat_map(|(_, shortcuts)| shortcuts.iter().map(|(action, _)| *action))
+            .collect();
+        let bound: Vec<&str> = SHORTCUTS.iter().map(|(action, _)| *action).collect();
+        assert_eq!(shown.len(), bound.len());
+        for action in bound {
+            assert!(shown.contains(&action), "{} is missing from the shortcuts window", action);
+        }
+        assert!(SHORTCUTS.iter().all(|(_, accels)| !accels.is_empty()));
+    }
 }
 
 fn install_custom_css(window: &ApplicationWindow) {
@@ -372,15 +961,11 @@ fn install_custom_css(window: &ApplicationWindow) {
         entry.password-entry {
             border-radius: 10px;
             padding: 6px 12px;
+            font-family: monospace;
         }
 
-        .copy-feedback-box {
-            margin-top: -4px;
-        }
-
-        .copy-feedback-label,
-        .copy-feedback-icon {
-            color: @theme_selected_bg_color;
+        .breach-warning {
+            color: @error_color;
             font-weight: 600;
         }
         "#;
@@ -388,6 +973,95 @@ fn install_custom_css(window: &ApplicationWindow) {
     let _ = provider.load_from_data(css);
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum CharClass {
+    Lowercase,
+    Uppercase,
+    Digit,
+    Special,
+}
+
+impl CharClass {
+    fn of(c: char) -> Option<Self> {
+        match c {
+            'a'..='z' => Some(CharClass::Lowercase),
+            'A'..='Z' => Some(CharClass::Uppercase),
+            '0'..='9' => Some(CharClass::Digit),
+            c if c.is_ascii() && SPECIAL.contains(&(c as u8)) => Some(CharClass::Special),
+            _ => None,
+        }
+    }
+
+    /// Foreground color from the GNOME palette; lowercase keeps the theme's text color.
+    fn color(self, dark_theme: bool) -> Option<u32> {
+        match (self, dark_theme) {
+            (CharClass::Lowercase, _) => None,
+            (CharClass::Uppercase, false) => Some(0x1a5fb4),
+            (CharClass::Uppercase, true) => Some(0x99c1f1),
+            (CharClass::Digit, false) => Some(0xc64600),
+            (CharClass::Digit, true) => Some(0xffbe6f),
+            (CharClass::Special, false) => Some(0x813d9c),
+            (CharClass::Special, true) => Some(0xdc8add),
+        }
+    }
+}
+
+/// Byte ranges of `text` by character class, with neighbours of the same class merged.
+fn class_runs(text: &str) -> Vec<(usize, usize, CharClass)> {
+    let mut runs: Vec<(usize, usize, CharClass)> = Vec::new();
+    for (index, c) in text.char_indices() {
+        let class = match CharClass::of(c) {
+            Some(class) => class,
+            None => continue,
+        };
+        let end = index + c.len_utf8();
+        match runs.last_mut() {
+            Some(run) if run.1 == index && run.2 == class => run.1 = end,
+            _ => runs.push((index, end, class)),
+        }
+    }
+    runs
+}
+
+/// Draws the share of the auto-close countdown still left as an arc starting
+/// at the top, over a faint full ring.
+fn draw_countdown_ring(cr: &gtk::cairo::Context, color: &gtk::gdk::RGBA, fraction: f64, width: i32, height: i32) {
+    let line_width = 2.5;
+    let (x, y) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+    let radius = x.min(y) - line_width / 2.0;
+    let top = -std::f64::consts::FRAC_PI_2;
+    let (red, green, blue) = (f64::from(color.red()), f64::from(color.green()), f64::from(color.blue()));
+    cr.set_line_width(line_width);
+
+    cr.set_source_rgba(red, green, blue, f64::from(color.alpha()) * 0.25);
+    cr.arc(x, y, radius, 0.0, std::f64::consts::TAU);
+    let _ = cr.stroke();
+
+    if fraction > 0.0 {
+        cr.set_source_rgba(red, green, blue, f64::from(color.alpha()));
+        cr.arc(x, y, radius, top, top + fraction * std::f64::consts::TAU);
+        let _ = cr.stroke();
+    }
+}
+
+/// Colors the entry's characters by class, so `0`/`O` and `1`/`l` stand apart.
+fn colorize_password_entry(entry: &Entry, enabled: bool) {
+    let attributes = gtk::pango::AttrList::new();
+    if enabled {
+        let dark_theme = adw::StyleManager::default().is_dark();
+        for (start, end, class) in class_runs(&entry.text()) {
+            if let Some(rgb) = class.color(dark_theme) {
+                let channel = |shift: u32| ((rgb >> shift) & 0xff) as u16 * 257;
+                let mut attribute = gtk::pango::AttrColor::new_foreground(channel(16), channel(8), channel(0));
+                attribute.set_start_index(start as u32);
+                attribute.set_end_index(end as u32);
+                attributes.insert(attribute);
+            }
+        }
+    }
+    entry.set_attributes(&attributes);
+}
+
 fn get_config_path() -> PathBuf {
     let mut path = glib::user_config_dir();
     path.push("passwordgenerator");
@@ -414,9 +1088,121 @@ fn save_settings(settings: &AppSettings) {
 }
 
 fn main() {
-    let app = Application::builder()
-        .application_id(APP_ID)
-        .build();
+    logging::init();
+    i18n::init();
+    // Applied before anything is translated; --lang may still override it
+    if let Some(language) = load_settings().language.filter(|language| i18n::is_available(language)) {
+        i18n::set_language(Some(&language));
+    }
+
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some(breach::BUILD_FILTER_COMMAND) {
+        std::process::exit(breach::run_build_filter(&args[2..]));
+    }
+
+    let app = Application::new(APP_ID, gio::ApplicationFlags::HANDLES_COMMAND_LINE);
+
+    app.add_main_option(
+        "debug",
+        glib::Char::from(b'd'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Enable debug logging",
+        None,
+    );
+    app.add_main_option(
+        "groups",
+        glib::Char::from(b'g'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Int,
+        "Generate a new password with N groups",
+        Some("N"),
+    );
+    app.add_main_option(
+        "copy",
+        glib::Char::from(b'c'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::None,
+        "Copy the password to the clipboard",
+        None,
+    );
+    app.add_main_option(
+        "pass",
+        glib::Char::from(b'p'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Generate a password with the saved settings and insert it into pass",
+        Some("PATH"),
+    );
+    app.add_main_option(
+        "count",
+        glib::Char::from(b'n'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Int,
+        "Generate N passwords with the saved settings and export them",
+        Some("N"),
+    );
+    app.add_main_option(
+        "format",
+        glib::Char::from(b'f'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Export format for --count: csv, json, bitwarden or 1password",
+        Some("FORMAT"),
+    );
+    app.add_main_option(
+        "output",
+        glib::Char::from(b'o'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Filename,
+        "Write the --count export to FILE instead of stdout",
+        Some("FILE"),
+    );
+    app.add_main_option(
+        "usernames",
+        glib::Char::from(b'u'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::Filename,
+        "Pair the --count passwords with the usernames in FILE, one per line",
+        Some("FILE"),
+    );
+    app.add_main_option(
+        "lang",
+        glib::Char::from(b'l'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Use LANG instead of the saved or desktop language, e.g. de",
+        Some("LANG"),
+    );
+    app.connect_handle_local_options(|_, options| {
+        if options.contains("debug") {
+            logging::set_debug_enabled(true);
+        }
+        if let Ok(Some(language)) = options.lookup::<String>("lang") {
+            if !i18n::is_available(&language) {
+                let codes: Vec<&str> = i18n::LANGUAGES.iter().map(|(code, _)| *code).collect();
+                eprintln!("--lang must be one of {}", codes.join(", "));
+                return 1;
+            }
+            i18n::set_language(Some(&language));
+        }
+        if let Ok(Some(name)) = options.lookup::<String>("pass") {
+            return insert_into_pass(&name);
+        }
+        if let Ok(Some(count)) = options.lookup::<i32>("count") {
+            let format = options.lookup::<String>("format").ok().flatten();
+            let output = options.lookup::<PathBuf>("output").ok().flatten();
+            let usernames = options.lookup::<PathBuf>("usernames").ok().flatten();
+            return export_bulk(count, format.as_deref(), output.as_deref(), usernames.as_deref());
+        }
+        if let Ok(Some(groups)) = options.lookup::<i32>("groups") {
+            if !(1..=MAX_GROUPS).contains(&groups) {
+                eprintln!("--groups must be between 1 and {}", MAX_GROUPS);
+                return 1;
+            }
+        }
+        -1
+    });
 
     let app_weak = app.downgrade();
     let quit_action = SimpleAction::new("quit", None);
@@ -426,19 +1212,103 @@ fn main() {
         }
     });
     app.add_action(&quit_action);
-    app.set_accels_for_action("app.quit", &["<Control>q"]);
+    for (action, accels) in SHORTCUTS {
+        app.set_accels_for_action(action, accels);
+    }
+
+    app.connect_startup(|app| {
+        if app.flags().contains(gio::ApplicationFlags::IS_SERVICE) {
+            // D-Bus activated: stay around briefly for follow-up calls, then exit
+            app.set_inactivity_timeout(SERVICE_INACTIVITY_TIMEOUT_MS);
+        } else {
+            bind_global_shortcut(app);
+        }
+
+        // Notification buttons can only trigger application actions, and may
+        // start a new instance that has no window yet
+        for (name, window_action) in [("copy-again", "win.copy-again"), ("generate-new", "win.copy-new")] {
+            let app_weak = app.downgrade();
+            let action = SimpleAction::new(name, None);
+            action.connect_activate(move |_, _| {
+                let app = match app_weak.upgrade() {
+                    Some(app) => app,
+                    None => return,
+                };
+                if app.active_window().is_none() {
+                    app.activate();
+                }
+                if let Some(window) = app.active_window() {
+                    // Queued behind the initial password of a freshly built window
+                    glib::idle_add_local_once(move || {
+                        let _ = window.activate_action(window_action, None);
+                    });
+                }
+            });
+            app.add_action(&action);
+        }
+    });
 
-    app.connect_activate(build_ui);
+    // Later launches are forwarded here by the primary instance, which reuses its window
+    app.connect_command_line(|app, command_line| {
+        let options = command_line.options_dict();
+        let groups = options.lookup::<i32>("groups").ok().flatten();
+        let copy = options.contains("copy");
+        let language = options.lookup::<String>("lang").ok().flatten();
+        app.activate();
+
+        if let Some(window) = app.active_window() {
+            // Queued behind the initial password of a freshly built window
+            glib::idle_add_local_once(move || {
+                if let Some(language) = language {
+                    let _ = window.activate_action("win.language", Some(&language.to_variant()));
+                }
+                if let Some(groups) = groups {
+                    let _ = window.activate_action("win.regenerate", Some(&groups.to_variant()));
+                }
+                if copy {
+                    let _ = window.activate_action("win.copy", None);
+                }
+            });
+        }
+        0
+    });
+    app.connect_activate(|app| match app.active_window() {
+        Some(window) => window.present(),
+        None => build_ui(app),
+    });
 
     app.run();
 }
 
+/// Binds the portal shortcut that generates and copies a password without
+/// focusing the window.
+fn bind_global_shortcut(app: &Application) {
+    let app_weak = app.downgrade();
+    glib::spawn_future_local(async move {
+        let description = localized_strings().global_shortcut_description;
+        let bound = match GlobalShortcuts::session().await {
+            Ok(shortcuts) => {
+                shortcuts
+                    .bind(description, move || {
+                        if let Some(window) = app_weak.upgrade().and_then(|app| app.active_window()) {
+                            let _ = window.activate_action("win.copy-new", None);
+                        }
+                    })
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = bound {
+            // Older desktops and sessions without xdg-desktop-portal have no such portal
+            glib::g_info!(LOG_DOMAIN, "Global shortcut unavailable: {}", err);
+        }
+    });
+}
+
 fn build_ui(app: &Application) {
-    let strings = Rc::new(localized_strings());
+    let strings = LiveStrings::new();
     let settings = Rc::new(RefCell::new(load_settings()));
 
-    ensure_system_color_scheme();
-
     let mut needs_charset_save = false;
     {
         let mut config = settings.borrow_mut();
@@ -457,10 +1327,13 @@ fn build_ui(app: &Application) {
 
     let window = ApplicationWindow::builder()
         .application(app)
-        .title(strings.app_title)
         .default_width(420)
         .default_height(320)
         .build();
+    strings.bind(&window, |window, strings| {
+        window.set_title(Some(strings.app_title));
+        window.set_help_overlay(Some(&shortcuts_window(strings)));
+    });
 
     let window_weak = window.downgrade();
     let close_action = SimpleAction::new("close", None);
@@ -470,10 +1343,29 @@ fn build_ui(app: &Application) {
         }
     });
     window.add_action(&close_action);
-    app.set_accels_for_action("win.close", &["<Control>w"]);
 
     install_custom_css(&window);
 
+    let header_bar = adw::HeaderBar::new();
+
+    let btn_analyze = ToggleButton::new();
+    strings.bind(&btn_analyze, |btn, strings| btn.set_label(strings.analyze_toggle_label));
+    header_bar.pack_start(&btn_analyze);
+
+    let btn_qr = ToggleButton::new();
+    strings.bind(&btn_qr, |btn, strings| btn.set_label(strings.qr_toggle_label));
+    header_bar.pack_start(&btn_qr);
+
+    let btn_menu = gtk::MenuButton::builder()
+        .icon_name("open-menu-symbolic")
+        .primary(true)
+        .build();
+    strings.bind(&btn_menu, |btn, strings| {
+        btn.set_menu_model(Some(&main_menu(strings)));
+        btn.set_tooltip_text(Some(strings.main_menu_tooltip));
+    });
+    header_bar.pack_end(&btn_menu);
+
     let box_container = gtk::Box::builder()
         .orientation(Orientation::Vertical)
         .spacing(15)
@@ -483,7 +1375,13 @@ fn build_ui(app: &Application) {
         .margin_end(20)
         .build();
 
-    window.set_child(Some(&box_container));
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&header_bar);
+    toolbar_view.set_content(Some(&box_container));
+
+    let toast_overlay = adw::ToastOverlay::new();
+    toast_overlay.set_child(Some(&toolbar_view));
+    window.set_content(Some(&toast_overlay));
 
     let entry = Entry::builder()
         .editable(false)
@@ -492,6 +1390,25 @@ fn build_ui(app: &Application) {
     gtk::prelude::EntryExt::set_alignment(&entry, 0.5);
     box_container.append(&entry);
 
+    let breach_badge = Label::new(None);
+    strings.bind(&breach_badge, |badge, strings| {
+        badge.set_label(&format!("⚠️ {}", strings.breach_warning_label))
+    });
+    breach_badge.add_css_class("breach-warning");
+    breach_badge.set_visible(false);
+    box_container.append(&breach_badge);
+
+    let lbl_strength = Label::new(None);
+    lbl_strength.add_css_class("dim-label");
+    box_container.append(&lbl_strength);
+
+    let lbl_feedback = Label::builder()
+        .wrap(true)
+        .justify(gtk::Justification::Center)
+        .visible(false)
+        .build();
+    box_container.append(&lbl_feedback);
+
     let controls_box = gtk::Box::builder()
         .orientation(Orientation::Horizontal)
         .spacing(10)
@@ -499,165 +1416,563 @@ fn build_ui(app: &Application) {
         .build();
     box_container.append(&controls_box);
 
-    let adjustment = Adjustment::new(settings.borrow().groups as f64, 1.0, 10.0, 1.0, 1.0, 0.0);
+    let adjustment = Adjustment::new(settings.borrow().groups as f64, 1.0, MAX_GROUPS as f64, 1.0, 1.0, 0.0);
     let spin_len = SpinButton::new(Some(&adjustment), 1.0, 0);
-    spin_len.set_tooltip_text(Some(strings.groups_tooltip));
+    strings.bind(&spin_len, |spin, strings| spin.set_tooltip_text(Some(strings.groups_tooltip)));
     controls_box.append(&spin_len);
 
-    let btn_gen = Button::with_label(strings.generate_button);
+    let btn_gen = Button::new();
+    strings.bind(&btn_gen, |btn, strings| btn.set_label(strings.generate_button));
+    btn_gen.add_css_class("suggested-action");
     controls_box.append(&btn_gen);
 
-    let btn_copy = Button::with_label(strings.copy_button);
+    let btn_copy = Button::new();
+    strings.bind(&btn_copy, |btn, strings| btn.set_label(strings.copy_button));
     controls_box.append(&btn_copy);
 
-    let copy_feedback_revealer = Revealer::builder()
-        .transition_type(RevealerTransitionType::Crossfade)
-        .reveal_child(false)
-        .build();
-    let copy_feedback_box = gtk::Box::builder()
-        .orientation(Orientation::Horizontal)
+    let btn_autotype = Button::new();
+    strings.bind(&btn_autotype, |btn, strings| btn.set_label(strings.autotype_button));
+    btn_autotype.set_action_name(Some("win.autotype"));
+    controls_box.append(&btn_autotype);
+
+    let qr_box = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
         .spacing(6)
         .halign(gtk::Align::Center)
         .build();
-    copy_feedback_box.add_css_class("copy-feedback-box");
-    let copy_feedback_icon = Label::new(Some("✅"));
-    copy_feedback_icon.add_css_class("copy-feedback-icon");
-    let copy_feedback_label = Label::new(Some(strings.copy_success_label));
-    copy_feedback_label.add_css_class("copy-feedback-label");
-    copy_feedback_box.append(&copy_feedback_icon);
-    copy_feedback_box.append(&copy_feedback_label);
-    copy_feedback_revealer.set_child(Some(&copy_feedback_box));
-    box_container.append(&copy_feedback_revealer);
-
-    let charset_section = gtk::Box::builder()
-        .orientation(Orientation::Vertical)
-        .spacing(6)
+    let qr_area = gtk::DrawingArea::builder()
+        .content_width(220)
+        .content_height(220)
         .build();
-    let charset_label = Label::new(Some(strings.charset_section_label));
-    charset_label.set_halign(gtk::Align::Start);
-    charset_section.append(&charset_label);
-
-    let charset_flow = FlowBox::builder()
-        .column_spacing(12)
-        .row_spacing(6)
-        .selection_mode(SelectionMode::None)
-        .max_children_per_line(2)
+    qr_box.append(&qr_area);
+    let chk_qr_wifi = CheckButton::new();
+    strings.bind(&chk_qr_wifi, |chk, strings| chk.set_label(Some(strings.qr_wifi_label)));
+    qr_box.append(&chk_qr_wifi);
+    let entry_qr_ssid = Entry::builder().visible(false).build();
+    strings.bind(&entry_qr_ssid, |entry, strings| {
+        entry.set_placeholder_text(Some(strings.qr_ssid_placeholder))
+    });
+    qr_box.append(&entry_qr_ssid);
+    let qr_revealer = Revealer::builder()
+        .transition_type(RevealerTransitionType::SlideDown)
+        .reveal_child(false)
+        .child(&qr_box)
         .build();
+    box_container.append(&qr_revealer);
 
-    let chk_lowercase = CheckButton::with_label(strings.lowercase_label);
-    chk_lowercase.set_active(settings.borrow().allow_lowercase);
-    charset_flow.insert(&chk_lowercase, -1);
-
-    let chk_uppercase = CheckButton::with_label(strings.uppercase_label);
-    chk_uppercase.set_active(settings.borrow().allow_uppercase);
-    charset_flow.insert(&chk_uppercase, -1);
-
-    let chk_digits = CheckButton::with_label(strings.digits_label);
-    chk_digits.set_active(settings.borrow().allow_digits);
-    charset_flow.insert(&chk_digits, -1);
-
-    let chk_special = CheckButton::with_label(strings.special_label);
-    chk_special.set_active(settings.borrow().allow_special);
-    charset_flow.insert(&chk_special, -1);
-
-    charset_section.append(&charset_flow);
-    box_container.append(&charset_section);
+    let lbl_spelling = Label::builder()
+        .wrap(true)
+        .justify(gtk::Justification::Center)
+        .selectable(true)
+        .build();
+    let spelling_expander = gtk::Expander::builder().child(&lbl_spelling).build();
+    strings.bind(&spelling_expander, |expander, strings| {
+        expander.set_label(Some(strings.spell_out_label))
+    });
+    box_container.append(&spelling_expander);
 
-    let status_box = gtk::Box::builder()
+    let timer_box = gtk::Box::builder()
         .orientation(Orientation::Horizontal)
-        .spacing(15)
+        .spacing(6)
         .halign(gtk::Align::Center)
         .build();
-    box_container.append(&status_box);
-
-    let chk_auto_close = CheckButton::with_label(strings.auto_close_label);
-    chk_auto_close.set_active(settings.borrow().auto_close);
-    status_box.append(&chk_auto_close);
-
-    let chk_copy_immediately = CheckButton::with_label(strings.copy_immediately_label);
-    chk_copy_immediately.set_active(settings.borrow().copy_immediately);
-    status_box.append(&chk_copy_immediately);
-
-    let chk_default_strategy = CheckButton::with_label(strings.default_strategy_label);
-    chk_default_strategy.set_active(settings.borrow().default_strategy);
-    status_box.append(&chk_default_strategy);
-
+    let timer_ring = gtk::DrawingArea::builder()
+        .content_width(16)
+        .content_height(16)
+        .visible(false)
+        .build();
+    timer_box.append(&timer_ring);
     let lbl_timer = Label::new(None);
-    status_box.append(&lbl_timer);
+    lbl_timer.add_css_class("dim-label");
+    timer_box.append(&lbl_timer);
+    box_container.append(&timer_box);
+
+    // Rarely changed options live in the preferences window, which is kept
+    // around while the main window is open so its rows can drive the actions
+    let row_default_strategy = adw::SwitchRow::builder()
+        .active(settings.borrow().default_strategy)
+        .build();
+    strings.bind(&row_default_strategy, |row, strings| row.set_title(strings.default_strategy_label));
+    let row_lowercase = adw::SwitchRow::builder()
+        .active(settings.borrow().allow_lowercase)
+        .build();
+    strings.bind(&row_lowercase, |row, strings| row.set_title(strings.lowercase_label));
+    let row_uppercase = adw::SwitchRow::builder()
+        .active(settings.borrow().allow_uppercase)
+        .build();
+    strings.bind(&row_uppercase, |row, strings| row.set_title(strings.uppercase_label));
+    let row_digits = adw::SwitchRow::builder()
+        .active(settings.borrow().allow_digits)
+        .build();
+    strings.bind(&row_digits, |row, strings| row.set_title(strings.digits_label));
+    let row_special = adw::SwitchRow::builder()
+        .active(settings.borrow().allow_special)
+        .build();
+    strings.bind(&row_special, |row, strings| row.set_title(strings.special_label));
+    let charset_group = adw::PreferencesGroup::new();
+    strings.bind(&charset_group, |group, strings| group.set_title(strings.charset_section_label));
+    charset_group.add(&row_default_strategy);
+    charset_group.add(&row_lowercase);
+    charset_group.add(&row_uppercase);
+    charset_group.add(&row_digits);
+    charset_group.add(&row_special);
+
+    // The first entry follows the desktop, the rest are `i18n::LANGUAGES`
+    let language_names = gtk::StringList::new(&[""]);
+    for (_, name) in i18n::LANGUAGES {
+        language_names.append(name);
+    }
+    let row_language = adw::ComboRow::builder()
+        .model(&language_names)
+        .selected(
+            settings
+                .borrow()
+                .language
+                .as_deref()
+                .and_then(|language| i18n::LANGUAGES.iter().position(|(code, _)| *code == language))
+                .map_or(0, |index| index as u32 + 1),
+        )
+        .build();
+    // Replacing the label of the selected entry resets the selection, which
+    // must not count as the user picking a language
+    let language_guard = Rc::new(Cell::new(false));
+    let language_guard_for_label = language_guard.clone();
+    strings.bind(&row_language, move |row, strings| {
+        row.set_title(strings.language_label);
+        let selected = row.selected();
+        language_guard_for_label.set(true);
+        language_names.splice(0, 1, &[strings.language_system_label]);
+        row.set_selected(selected);
+        language_guard_for_label.set(false);
+    });
+    let row_copy_immediately = adw::SwitchRow::builder()
+        .active(settings.borrow().copy_immediately)
+        .build();
+    strings.bind(&row_copy_immediately, |row, strings| row.set_title(strings.copy_immediately_label));
+    let row_auto_close = adw::SwitchRow::builder()
+        .active(settings.borrow().auto_close)
+        .build();
+    strings.bind(&row_auto_close, |row, strings| row.set_title(strings.auto_close_label));
+    let row_auto_close_sec = adw::SpinRow::builder()
+        .adjustment(&Adjustment::new(
+            settings.borrow().auto_close_sec as f64,
+            1.0,
+            MAX_AUTO_CLOSE_SEC as f64,
+            1.0,
+            10.0,
+            0.0,
+        ))
+        .build();
+    strings.bind(&row_auto_close_sec, |row, strings| row.set_title(strings.auto_close_duration_label));
+    let row_auto_close_pause = adw::SwitchRow::builder()
+        .active(settings.borrow().auto_close_pause)
+        .build();
+    strings.bind(&row_auto_close_pause, |row, strings| row.set_title(strings.auto_close_pause_label));
+    let row_close_after_copy = adw::SwitchRow::builder()
+        .active(settings.borrow().close_after_copy)
+        .build();
+    strings.bind(&row_close_after_copy, |row, strings| row.set_title(strings.close_after_copy_label));
+    let row_hide_on_auto_close = adw::SwitchRow::builder()
+        .active(settings.borrow().hide_on_auto_close)
+        .build();
+    strings.bind(&row_hide_on_auto_close, |row, strings| row.set_title(strings.hide_on_auto_close_label));
+    let row_notifications = adw::SwitchRow::builder()
+        .active(settings.borrow().notifications)
+        .build();
+    strings.bind(&row_notifications, |row, strings| row.set_title(strings.notifications_label));
+    let row_clear_clipboard = adw::SpinRow::builder()
+        .adjustment(&Adjustment::new(
+            settings.borrow().clear_clipboard_sec as f64,
+            0.0,
+            MAX_CLEAR_CLIPBOARD_SEC as f64,
+            1.0,
+            10.0,
+            0.0,
+        ))
+        .build();
+    strings.bind(&row_clear_clipboard, |row, strings| {
+        row.set_title(strings.clear_clipboard_label);
+        row.set_subtitle(strings.clear_clipboard_subtitle);
+    });
+    let row_colorize = adw::SwitchRow::builder()
+        .active(settings.borrow().colorize_password)
+        .build();
+    strings.bind(&row_colorize, |row, strings| row.set_title(strings.colorize_label));
+    let row_autotype_delay = adw::SpinRow::builder()
+        .adjustment(&Adjustment::new(
+            settings.borrow().autotype_delay_sec as f64,
+            0.0,
+            MAX_AUTOTYPE_DELAY_SEC as f64,
+            1.0,
+            5.0,
+            0.0,
+        ))
+        .build();
+    strings.bind(&row_autotype_delay, |row, strings| {
+        row.set_title(strings.autotype_delay_label);
+        row.set_subtitle(strings.autotype_delay_tooltip);
+    });
+    let behavior_group = adw::PreferencesGroup::new();
+    strings.bind(&behavior_group, |group, strings| group.set_title(strings.preferences_behavior_group));
+    behavior_group.add(&row_language);
+    behavior_group.add(&row_copy_immediately);
+    behavior_group.add(&row_auto_close);
+    behavior_group.add(&row_auto_close_sec);
+    behavior_group.add(&row_auto_close_pause);
+    behavior_group.add(&row_close_after_copy);
+    behavior_group.add(&row_hide_on_auto_close);
+    behavior_group.add(&row_notifications);
+    behavior_group.add(&row_clear_clipboard);
+    behavior_group.add(&row_colorize);
+    behavior_group.add(&row_autotype_delay);
+
+    let row_breach_source = adw::ActionRow::builder()
+        .activatable(true)
+        .build();
+    strings.bind(&row_breach_source, |row, strings| row.set_title(strings.breach_source_button));
+    row_breach_source.add_suffix(&gtk::Image::from_icon_name("document-open-symbolic"));
+    if let Some(name) = settings.borrow().breach_database.as_ref().and_then(|path| path.file_name()) {
+        row_breach_source.set_subtitle(&name.to_string_lossy());
+    }
+    let breach_group = adw::PreferencesGroup::new();
+    strings.bind(&breach_group, |group, strings| group.set_title(strings.preferences_breach_group));
+    breach_group.add(&row_breach_source);
+
+    let preferences_page = adw::PreferencesPage::new();
+    preferences_page.add(&charset_group);
+    preferences_page.add(&behavior_group);
+    preferences_page.add(&breach_group);
+
+    let preferences_window = adw::PreferencesWindow::builder()
+        .transient_for(&window)
+        .modal(true)
+        .hide_on_close(true)
+        .destroy_with_parent(true)
+        .search_enabled(false)
+        .build();
+    strings.bind(&preferences_window, |window, strings| window.set_title(Some(strings.preferences_title)));
+    preferences_window.add(&preferences_page);
 
     let runtime_auto_close_active = Rc::new(Cell::new(settings.borrow().auto_close));
-    let remaining = Rc::new(RefCell::new(CLOSE_AFTER_SEC));
+    let remaining = Rc::new(RefCell::new(settings.borrow().auto_close_sec));
+    // Counts down after a copy even when the auto-close timer itself is off
+    let close_after_copy_armed = Rc::new(Cell::new(false));
+    let hovered = Rc::new(Cell::new(false));
 
-    let gesture = GestureClick::new();
-    gesture.set_propagation_phase(PropagationPhase::Capture);
-    let chk_auto_close_weak = chk_auto_close.downgrade();
-    let lbl_timer_weak = lbl_timer.downgrade();
-    let runtime_auto_close_active_for_gesture = runtime_auto_close_active.clone();
-    gesture.connect_pressed(move |_, _, _, _| {
-        if let (Some(chk_auto_close), Some(lbl_timer)) = (
-            chk_auto_close_weak.upgrade(),
-            lbl_timer_weak.upgrade(),
-        ) {
-            if chk_auto_close.is_active() && runtime_auto_close_active_for_gesture.get() {
-                runtime_auto_close_active_for_gesture.set(false);
-                lbl_timer.set_label("");
+    // Without pausing, any interaction keeps the window open for the rest of the session
+    let stop_auto_close: Rc<dyn Fn()> = {
+        let settings = settings.clone();
+        let row_auto_close_weak = row_auto_close.downgrade();
+        let lbl_timer_weak = lbl_timer.downgrade();
+        let timer_ring_weak = timer_ring.downgrade();
+        let runtime_auto_close_active = runtime_auto_close_active.clone();
+        let close_after_copy_armed = close_after_copy_armed.clone();
+        Rc::new(move || {
+            if settings.borrow().auto_close_pause {
+                return;
+            }
+            if let (Some(row_auto_close), Some(lbl_timer), Some(timer_ring)) = (
+                row_auto_close_weak.upgrade(),
+                lbl_timer_weak.upgrade(),
+                timer_ring_weak.upgrade(),
+            ) {
+                if (row_auto_close.is_active() && runtime_auto_close_active.get()) || close_after_copy_armed.get() {
+                    runtime_auto_close_active.set(false);
+                    close_after_copy_armed.set(false);
+                    lbl_timer.set_label("");
+                    timer_ring.set_visible(false);
+                }
             }
+        })
+    };
+
+    let gesture = GestureClick::new();
+    gesture.set_propagation_phase(PropagationPhase::Capture);
+    let stop_auto_close_for_gesture = stop_auto_close.clone();
+    gesture.connect_pressed(move |_, _, _, _| stop_auto_close_for_gesture());
+    window.add_controller(gesture);
+
+    let motion = gtk::EventControllerMotion::new();
+    let hovered_for_enter = hovered.clone();
+    motion.connect_enter(move |_, _, _| hovered_for_enter.set(true));
+    let hovered_for_leave = hovered.clone();
+    motion.connect_leave(move |_| hovered_for_leave.set(false));
+    window.add_controller(motion);
+
+    let remaining_for_ring = remaining.clone();
+    let settings_for_ring = settings.clone();
+    timer_ring.set_draw_func(move |area, cr, width, height| {
+        let total = settings_for_ring.borrow().auto_close_sec.max(1);
+        let fraction = f64::from((*remaining_for_ring.borrow()).clamp(0, total)) / f64::from(total);
+        draw_countdown_ring(cr, &area.color(), fraction, width, height);
+    });
+
+    let preferences_window_for_action = preferences_window.clone();
+    let preferences_action = SimpleAction::new("preferences", None);
+    preferences_action.connect_activate(move |_, _| {
+        // The preferences window is modal, so clicks there never reach the gesture
+        // and with pausing enabled the countdown holds while it is open
+        stop_auto_close();
+        preferences_window_for_action.present();
+    });
+    window.add_action(&preferences_action);
+
+    // Not saved, so `--lang` only lasts for the session
+    let strings_for_language = strings.clone();
+    let row_language_for_action = row_language.clone();
+    let language_guard_for_action = language_guard.clone();
+    let language_action = SimpleAction::new("language", Some(glib::VariantTy::STRING));
+    language_action.connect_activate(move |_, parameter| {
+        if let Some(language) = parameter.and_then(|p| p.get::<String>()) {
+            i18n::set_language(Some(language.as_str()).filter(|language| !language.is_empty()));
+            strings_for_language.reload();
+            // Shows a language from `--lang` in the preferences without saving it
+            let selected = i18n::LANGUAGES
+                .iter()
+                .position(|(code, _)| *code == language)
+                .map_or(0, |index| index as u32 + 1);
+            language_guard_for_action.set(true);
+            row_language_for_action.set_selected(selected);
+            language_guard_for_action.set(false);
+            glib::g_info!(LOG_DOMAIN, "Switched the interface language");
+        }
+    });
+    window.add_action(&language_action);
+
+    let settings_for_language = settings.clone();
+    let window_weak_for_language = window.downgrade();
+    row_language.connect_selected_notify(move |row| {
+        if language_guard.get() {
+            return;
+        }
+        let language = (row.selected() as usize)
+            .checked_sub(1)
+            .and_then(|index| i18n::LANGUAGES.get(index))
+            .map(|(code, _)| *code);
+        settings_for_language.borrow_mut().language = language.map(str::to_owned);
+        save_settings(&settings_for_language.borrow());
+        if let Some(window) = window_weak_for_language.upgrade() {
+            let _ = window.activate_action("win.language", Some(&language.unwrap_or_default().to_variant()));
         }
     });
-    window.add_controller(gesture);
 
-    let pending_copy = Rc::new(RefCell::new(None::<String>));
+    let pending_copy = Rc::new(RefCell::new(None::<SecretString>));
 
-    let feedback_timeout = Rc::new(RefCell::new(None::<SourceId>));
-    let show_copy_feedback: Rc<dyn Fn()> = {
-        let revealer = copy_feedback_revealer.clone();
-        let timeout_handle = feedback_timeout.clone();
-        Rc::new(move || {
-            {
-                let mut handle = timeout_handle.borrow_mut();
-                if let Some(id) = handle.take() {
-                    id.remove();
+    let current_toast = Rc::new(RefCell::new(None::<adw::Toast>));
+    let show_feedback: Rc<dyn Fn(&str)> = {
+        let toast_overlay = toast_overlay.clone();
+        Rc::new(move |message: &str| {
+            // Replace the previous toast instead of queueing behind it
+            if let Some(toast) = current_toast.borrow_mut().take() {
+                toast.dismiss();
+            }
+            let toast = adw::Toast::builder()
+                .title(message)
+                .use_markup(false)
+                .timeout(2)
+                .build();
+            toast_overlay.add_toast(toast.clone());
+            current_toast.replace(Some(toast));
+        })
+    };
+    // Shown in the window while it has focus, as a desktop notification otherwise
+    let notify: Rc<dyn Fn(&str, &str, &[(&str, &str)])> = {
+        let window_weak = window.downgrade();
+        let settings = settings.clone();
+        let strings = strings.clone();
+        let show_feedback = show_feedback.clone();
+        Rc::new(move |id: &str, body: &str, buttons: &[(&str, &str)]| {
+            let window = match window_weak.upgrade() {
+                Some(w) => w,
+                None => return,
+            };
+            if window_is_active(&window) {
+                show_feedback(body);
+            } else if settings.borrow().notifications {
+                if let Some(app) = window.application() {
+                    send_notification(&app, strings.current().app_title, id, body, buttons);
                 }
             }
-            revealer.set_reveal_child(true);
-            let timeout_handle_clone = timeout_handle.clone();
-            let revealer_clone = revealer.clone();
-            let source_id = glib::timeout_add_local(Duration::from_millis(1500), move || {
-                revealer_clone.set_reveal_child(false);
-                timeout_handle_clone.borrow_mut().take();
-                glib::ControlFlow::Break
+        })
+    };
+
+    // Wayland ignores clipboard changes from unfocused windows, so a clear is
+    // only confirmed, and announced, once it happens with focus. Until then it
+    // stays due and is retried whenever the window gets focus again.
+    let clipboard_clear_due = Rc::new(Cell::new(false));
+    let clear_due_clipboard: Rc<dyn Fn()> = {
+        let window_weak = window.downgrade();
+        let strings = strings.clone();
+        let notify = notify.clone();
+        let clipboard_clear_due = clipboard_clear_due.clone();
+        Rc::new(move || {
+            let window = match window_weak.upgrade() {
+                Some(w) => w,
+                None => return,
+            };
+            if !clipboard_clear_due.get() {
+                return;
+            }
+            if !window_is_active(&window) {
+                // Enough where unfocused windows may change the clipboard, e.g. on X11
+                clear_clipboard();
+                return;
+            }
+            clipboard_clear_due.set(false);
+            if clear_clipboard() {
+                glib::g_info!(LOG_DOMAIN, "Clipboard cleared");
+                notify(
+                    CLIPBOARD_NOTIFICATION_ID,
+                    strings.current().notification_cleared_body,
+                    &[(strings.current().notification_generate_new, "app.generate-new")],
+                );
+            }
+        })
+    };
+
+    // The last copied password, kept for "Copy again" until the clipboard is cleared
+    let clipboard_secret = Rc::new(RefCell::new(None::<SecretString>));
+    let clipboard_clear_timeout = Rc::new(RefCell::new(None::<SourceId>));
+    let track_clipboard: Rc<dyn Fn(&SecretString)> = {
+        let settings = settings.clone();
+        let clipboard_secret = clipboard_secret.clone();
+        let clipboard_clear_timeout = clipboard_clear_timeout.clone();
+        let clipboard_clear_due = clipboard_clear_due.clone();
+        let clear_due_clipboard = clear_due_clipboard.clone();
+        Rc::new(move |text: &SecretString| {
+            clipboard_secret.replace(Some(SecretString::from(text.as_str())));
+            clipboard_clear_due.set(false);
+            if let Some(id) = clipboard_clear_timeout.borrow_mut().take() {
+                id.remove();
+            }
+            let seconds = settings.borrow().clear_clipboard_sec;
+            if seconds == 0 {
+                return;
+            }
+            let clipboard_secret = clipboard_secret.clone();
+            let clipboard_clear_timeout_for_source = clipboard_clear_timeout.clone();
+            let clipboard_clear_due = clipboard_clear_due.clone();
+            let clear_due_clipboard = clear_due_clipboard.clone();
+            let source_id = glib::timeout_add_seconds_local_once(seconds, move || {
+                clipboard_clear_timeout_for_source.borrow_mut().take();
+                clipboard_secret.borrow_mut().take();
+                clipboard_clear_due.set(true);
+                clear_due_clipboard();
             });
-            timeout_handle.borrow_mut().replace(source_id);
+            clipboard_clear_timeout.replace(Some(source_id));
+        })
+    };
+
+    // Every successful copy from the window ends up here
+    let on_copied: Rc<dyn Fn(&SecretString)> = {
+        let show_feedback = show_feedback.clone();
+        let track_clipboard = track_clipboard.clone();
+        let strings = strings.clone();
+        let settings = settings.clone();
+        let remaining = remaining.clone();
+        let close_after_copy_armed = close_after_copy_armed.clone();
+        Rc::new(move |text: &SecretString| {
+            show_feedback(&format!("✅ {}", strings.current().copy_success_label));
+            track_clipboard(text);
+            let config = settings.borrow();
+            if config.close_after_copy {
+                close_after_copy_armed.set(true);
+                *remaining.borrow_mut() = config.auto_close_sec;
+            }
+        })
+    };
+
+    let breach_database = Rc::new(RefCell::new(None::<BreachDatabase>));
+    if let Some(path) = settings.borrow().breach_database.as_ref() {
+        match BreachDatabase::open(path) {
+            Ok(database) => *breach_database.borrow_mut() = Some(database),
+            Err(err) => glib::g_warning!(LOG_DOMAIN, "Failed to open breach data: {}", err),
+        }
+    }
+
+    let refresh_breach_badge: Rc<dyn Fn()> = {
+        let entry = entry.clone();
+        let badge = breach_badge.clone();
+        let database = breach_database.clone();
+        Rc::new(move || {
+            let text = SecretString::from(entry.text().as_str());
+            let found = match database.borrow().as_ref() {
+                Some(database) if !text.as_str().is_empty() => {
+                    database.contains(text.as_str()).unwrap_or_else(|err| {
+                        glib::g_warning!(LOG_DOMAIN, "Breach check failed: {}", err);
+                        false
+                    })
+                }
+                _ => false,
+            };
+            badge.set_visible(found);
         })
     };
 
+    let refresh_breach_badge_for_entry = refresh_breach_badge.clone();
+    entry.connect_changed(move |_| refresh_breach_badge_for_entry());
+
+    let preferences_window_weak = preferences_window.downgrade();
+    let settings_for_breach = settings.clone();
+    let strings_for_breach = strings.clone();
+    row_breach_source.connect_activated(move |row| {
+        if let Some(preferences_window) = preferences_window_weak.upgrade() {
+            let dialog = gtk::FileDialog::builder()
+                .title(strings_for_breach.current().breach_source_button)
+                .modal(true)
+                .build();
+            let settings = settings_for_breach.clone();
+            let database = breach_database.clone();
+            let refresh_breach_badge = refresh_breach_badge.clone();
+            let row = row.clone();
+            dialog.open(Some(&preferences_window), None::<&gio::Cancellable>, move |result| {
+                // An error here means the dialog was dismissed
+                if let Some(path) = result.ok().and_then(|file| file.path()) {
+                    match BreachDatabase::open(&path) {
+                        Ok(opened) => {
+                            *database.borrow_mut() = Some(opened);
+                            if let Some(name) = path.file_name() {
+                                row.set_subtitle(&name.to_string_lossy());
+                            }
+                            settings.borrow_mut().breach_database = Some(path);
+                            save_settings(&settings.borrow());
+                            glib::g_info!(LOG_DOMAIN, "Loaded breach data");
+                            refresh_breach_badge();
+                        }
+                        Err(err) => glib::g_warning!(LOG_DOMAIN, "Failed to open breach data: {}", err),
+                    }
+                }
+            });
+        }
+    });
+
     let charset_guard = Rc::new(Cell::new(false));
-    let charset_buttons = Rc::new(vec![
-        chk_lowercase.clone(),
-        chk_uppercase.clone(),
-        chk_digits.clone(),
-        chk_special.clone(),
+    let charset_rows = Rc::new(vec![
+        row_lowercase.clone(),
+        row_uppercase.clone(),
+        row_digits.clone(),
+        row_special.clone(),
     ]);
 
     {
         let settings = settings.clone();
         let guard = charset_guard.clone();
-        let buttons = charset_buttons.clone();
-        let default_strategy_btn = chk_default_strategy.clone();
-        chk_lowercase.connect_toggled(move |btn| {
+        let rows = charset_rows.clone();
+        let default_strategy_row = row_default_strategy.clone();
+        row_lowercase.connect_active_notify(move |row| {
             if guard.get() {
                 return;
             }
-            if !buttons.iter().any(|b| b.is_active()) {
+            if !rows.iter().any(|r| r.is_active()) {
                 guard.set(true);
-                btn.set_active(true);
+                row.set_active(true);
                 guard.set(false);
                 return;
             }
-            let active = btn.is_active();
+            let active = row.is_active();
 
-            if !active && default_strategy_btn.is_active() {
-                default_strategy_btn.set_active(false);
+            if !active && default_strategy_row.is_active() {
+                default_strategy_row.set_active(false);
             }
 
             {
@@ -671,18 +1986,18 @@ fn build_ui(app: &Application) {
     {
         let settings = settings.clone();
         let guard = charset_guard.clone();
-        let buttons = charset_buttons.clone();
-        chk_uppercase.connect_toggled(move |btn| {
+        let rows = charset_rows.clone();
+        row_uppercase.connect_active_notify(move |row| {
             if guard.get() {
                 return;
             }
-            if !buttons.iter().any(|b| b.is_active()) {
+            if !rows.iter().any(|r| r.is_active()) {
                 guard.set(true);
-                btn.set_active(true);
+                row.set_active(true);
                 guard.set(false);
                 return;
             }
-            let active = btn.is_active();
+            let active = row.is_active();
             {
                 let mut config = settings.borrow_mut();
                 config.allow_uppercase = active;
@@ -694,18 +2009,18 @@ fn build_ui(app: &Application) {
     {
         let settings = settings.clone();
         let guard = charset_guard.clone();
-        let buttons = charset_buttons.clone();
-        chk_digits.connect_toggled(move |btn| {
+        let rows = charset_rows.clone();
+        row_digits.connect_active_notify(move |row| {
             if guard.get() {
                 return;
             }
-            if !buttons.iter().any(|b| b.is_active()) {
+            if !rows.iter().any(|r| r.is_active()) {
                 guard.set(true);
-                btn.set_active(true);
+                row.set_active(true);
                 guard.set(false);
                 return;
             }
-            let active = btn.is_active();
+            let active = row.is_active();
             {
                 let mut config = settings.borrow_mut();
                 config.allow_digits = active;
@@ -717,18 +2032,18 @@ fn build_ui(app: &Application) {
     {
         let settings = settings.clone();
         let guard = charset_guard.clone();
-        let buttons = charset_buttons;
-        chk_special.connect_toggled(move |btn| {
+        let rows = charset_rows;
+        row_special.connect_active_notify(move |row| {
             if guard.get() {
                 return;
             }
-            if !buttons.iter().any(|b| b.is_active()) {
+            if !rows.iter().any(|r| r.is_active()) {
                 guard.set(true);
-                btn.set_active(true);
+                row.set_active(true);
                 guard.set(false);
                 return;
             }
-            let active = btn.is_active();
+            let active = row.is_active();
             {
                 let mut config = settings.borrow_mut();
                 config.allow_special = active;
@@ -737,53 +2052,64 @@ fn build_ui(app: &Application) {
         });
     }
 
+    // Entropy of the generated password on display, to relabel its strength
+    let generated_bits = Rc::new(Cell::new(None::<f64>));
     let update_password = {
         let entry = entry.clone();
+        let settings = settings.clone();
         let remaining = remaining.clone();
         let window = window.clone();
-        let chk_copy_immediately = chk_copy_immediately.clone();
-        let chk_default_strategy = chk_default_strategy.clone();
+        let row_copy_immediately = row_copy_immediately.clone();
+        let row_default_strategy = row_default_strategy.clone();
         let pending_copy = pending_copy.clone();
         let runtime_auto_close_active = runtime_auto_close_active.clone();
-        let chk_auto_close = chk_auto_close.clone();
-        let chk_lowercase = chk_lowercase.clone();
-        let chk_uppercase = chk_uppercase.clone();
-        let chk_digits = chk_digits.clone();
-        let chk_special = chk_special.clone();
-        let show_copy_feedback = show_copy_feedback.clone();
+        let row_auto_close = row_auto_close.clone();
+        let row_lowercase = row_lowercase.clone();
+        let row_uppercase = row_uppercase.clone();
+        let row_digits = row_digits.clone();
+        let row_special = row_special.clone();
+        let on_copied = on_copied.clone();
         let strings = strings.clone();
+        let lbl_strength = lbl_strength.clone();
+        let generated_bits = generated_bits.clone();
         move |len: i32| {
             let options = GenerationOptions::new(
-                chk_lowercase.is_active(),
-                chk_uppercase.is_active(),
-                chk_digits.is_active(),
-                chk_special.is_active(),
+                row_lowercase.is_active(),
+                row_uppercase.is_active(),
+                row_digits.is_active(),
+                row_special.is_active(),
             );
 
-            let use_default_strategy = chk_default_strategy.is_active();
+            let use_default_strategy = row_default_strategy.is_active();
 
             if !use_default_strategy && !options.is_valid() {
                 entry.set_text("");
+                lbl_strength.set_label("");
+                generated_bits.set(None);
                 return;
             }
 
             let password = generate_password(len, &options, use_default_strategy);
-            entry.set_text(&password);
+            entry.set_text(password.as_str());
+            let bits = password_entropy_bits(len, &options, use_default_strategy);
+            lbl_strength.set_label(&strings.current().strength_label(bits));
+            generated_bits.set(Some(bits));
+            glib::g_debug!(LOG_DOMAIN, "Generated password with {} groups", len.max(1));
 
-            if chk_copy_immediately.is_active() {
+            if row_copy_immediately.is_active() {
                 // Always set pending_copy first, then schedule a delayed copy if window is active.
                 // This ensures Wayland has time to process focus before clipboard write.
-                *pending_copy.borrow_mut() = Some(password.clone());
+                *pending_copy.borrow_mut() = Some(password);
                 if window_is_active(&window) {
                     let window = window.clone();
                     let pending_copy = pending_copy.clone();
                     let strings = strings.clone();
-                    let show_copy_feedback = show_copy_feedback.clone();
+                    let on_copied = on_copied.clone();
                     glib::timeout_add_local_once(Duration::from_millis(50), move || {
                         if let Some(text) = pending_copy.borrow_mut().take() {
-                            copy_to_clipboard(&window, &text);
-                            println!("{}", strings.clipboard_log(&text));
-                            show_copy_feedback();
+                            copy_to_clipboard(&window, text.as_str());
+                            log_clipboard_copy(&strings.current(), &text);
+                            on_copied(&text);
                         }
                     });
                 }
@@ -792,44 +2118,501 @@ fn build_ui(app: &Application) {
                 pending_copy.borrow_mut().take();
             }
 
-            if runtime_auto_close_active.get() && chk_auto_close.is_active() {
-                *remaining.borrow_mut() = CLOSE_AFTER_SEC;
+            if runtime_auto_close_active.get() && row_auto_close.is_active() {
+                *remaining.borrow_mut() = settings.borrow().auto_close_sec;
             }
         }
     };
 
-    let update_password_for_button = update_password.clone();
+    let update_password_for_action = update_password.clone();
     let spin_len_weak = spin_len.downgrade();
-    btn_gen.connect_clicked(move |_| {
+    let generate_action = SimpleAction::new("generate", None);
+    generate_action.connect_activate(move |_, _| {
         if let Some(spin_len) = spin_len_weak.upgrade() {
-            update_password_for_button(spin_len.value() as i32);
+            update_password_for_action(spin_len.value() as i32);
         }
     });
+    window.add_action(&generate_action);
+    btn_gen.set_action_name(Some("win.generate"));
+
+    let mut groups_actions = Vec::new();
+    for (name, step) in [("more-groups", gtk::SpinType::StepForward), ("fewer-groups", gtk::SpinType::StepBackward)] {
+        let spin_len_for_action = spin_len.clone();
+        let action = SimpleAction::new(name, None);
+        action.connect_activate(move |_, _| spin_len_for_action.spin(step, 1.0));
+        window.add_action(&action);
+        groups_actions.push(action);
+    }
+
+    for (name, button) in [
+        ("lowercase", &row_lowercase),
+        ("uppercase", &row_uppercase),
+        ("digits", &row_digits),
+        ("special", &row_special),
+        ("auto-close", &row_auto_close),
+    ] {
+        window.add_action(&gio::PropertyAction::new(name, button, "active"));
+    }
 
     let entry_weak_for_copy = entry.downgrade();
     let window_weak_for_copy = window.downgrade();
     let strings_for_copy = strings.clone();
-    let pending_copy_for_button = pending_copy.clone();
-    let show_copy_feedback_for_button = show_copy_feedback.clone();
-    btn_copy.connect_clicked(move |_| {
+    let pending_copy_for_copy_action = pending_copy.clone();
+    let on_copied_for_copy_action = on_copied.clone();
+    let copy_action = SimpleAction::new("copy", None);
+    copy_action.connect_activate(move |_, _| {
         if let (Some(entry), Some(window)) = (
             entry_weak_for_copy.upgrade(),
             window_weak_for_copy.upgrade(),
         ) {
-            let text = entry.text().to_string();
+            if copy_focused_selection(&window, &entry) {
+                return;
+            }
+            let text = SecretString::from(entry.text().as_str());
             if window_is_active(&window) {
-                copy_to_clipboard(&window, &text);
-                println!("{}", strings_for_copy.clipboard_log(&text));
-                show_copy_feedback_for_button();
-                pending_copy_for_button.borrow_mut().take();
+                copy_to_clipboard(&window, text.as_str());
+                log_clipboard_copy(&strings_for_copy.current(), &text);
+                on_copied_for_copy_action(&text);
+                pending_copy_for_copy_action.borrow_mut().take();
             } else {
                 // Defer copying until the window gains focus (needed on Wayland)
-                *pending_copy_for_button.borrow_mut() = Some(text.clone());
+                *pending_copy_for_copy_action.borrow_mut() = Some(text);
                 window.present();
             }
         }
     });
 
+    window.add_action(&copy_action);
+    btn_copy.set_action_name(Some("win.copy"));
+
+    let window_weak_for_autotype = window.downgrade();
+    let entry_for_autotype = entry.clone();
+    let settings_for_autotype = settings.clone();
+    let strings_for_autotype = strings.clone();
+    let show_feedback_for_autotype = show_feedback.clone();
+    let notify_for_autotype = notify.clone();
+    let autotype_action = SimpleAction::new("autotype", None);
+    autotype_action.connect_activate(move |action, _| {
+        let window = match window_weak_for_autotype.upgrade() {
+            Some(w) => w,
+            None => return,
+        };
+        let text = SecretString::from(entry_for_autotype.text().as_str());
+        if text.as_str().is_empty() {
+            return;
+        }
+        let (delay, restore_token) = {
+            let config = settings_for_autotype.borrow();
+            (
+                Duration::from_secs(config.autotype_delay_sec.into()),
+                config.autotype_restore_token.clone(),
+            )
+        };
+
+        // Hand the focus back to the window the user came from
+        window.minimize();
+        action.set_enabled(false);
+        // Keeps typing alive even if auto-close shuts the window meanwhile
+        let hold = window.application().map(|app| app.hold());
+        let action = action.clone();
+        let settings = settings_for_autotype.clone();
+        let strings = strings_for_autotype.clone();
+        let show_feedback = show_feedback_for_autotype.clone();
+        let notify = notify_for_autotype.clone();
+        glib::spawn_future_local(async move {
+            let result = autotype::type_text(text, delay, restore_token).await;
+            action.set_enabled(true);
+            match result {
+                Ok(restore_token) => {
+                    glib::g_info!(LOG_DOMAIN, "Password typed into the focused window");
+                    settings.borrow_mut().autotype_restore_token = restore_token;
+                    save_settings(&settings.borrow());
+                    show_feedback(strings.current().autotype_done_label);
+                }
+                Err(err) => {
+                    glib::g_warning!(LOG_DOMAIN, "Auto-type failed: {}", err);
+                    let message = strings.current().autotype_error_label(&err.to_string());
+                    // Still minimized, so this becomes a notification
+                    notify("error", &message, &[]);
+                    window.present();
+                    show_feedback(&message);
+                }
+            }
+            drop(hold);
+        });
+    });
+    window.add_action(&autotype_action);
+
+    let settings_for_autotype_delay = settings.clone();
+    row_autotype_delay.connect_value_notify(move |row| {
+        settings_for_autotype_delay.borrow_mut().autotype_delay_sec = row.value() as u32;
+        save_settings(&settings_for_autotype_delay.borrow());
+    });
+
+    // Run from the global shortcut, so it copies without bringing the window
+    // forward and always confirms with a desktop notification
+    let window_weak_for_copy_new = window.downgrade();
+    let entry_for_copy_new = entry.clone();
+    let generate_action_for_copy_new = generate_action.clone();
+    let pending_copy_for_copy_new = pending_copy.clone();
+    let on_copied_for_copy_new = on_copied.clone();
+    let strings_for_copy_new = strings.clone();
+    let copy_new_action = SimpleAction::new("copy-new", None);
+    copy_new_action.connect_activate(move |_, _| {
+        let window = match window_weak_for_copy_new.upgrade() {
+            Some(w) => w,
+            None => return,
+        };
+        if !generate_action_for_copy_new.is_enabled() {
+            return;
+        }
+        generate_action_for_copy_new.activate(None);
+        let text = SecretString::from(entry_for_copy_new.text().as_str());
+        if text.as_str().is_empty() {
+            return;
+        }
+        copy_to_clipboard(&window, text.as_str());
+        log_clipboard_copy(&strings_for_copy_new.current(), &text);
+        pending_copy_for_copy_new.borrow_mut().take();
+        on_copied_for_copy_new(&text);
+        if let Some(app) = window.application() {
+            let strings = strings_for_copy_new.current();
+            send_notification(
+                &app,
+                strings.app_title,
+                CLIPBOARD_NOTIFICATION_ID,
+                strings.global_shortcut_notification,
+                &[
+                    (strings.notification_copy_again, "app.copy-again"),
+                    (strings.notification_generate_new, "app.generate-new"),
+                ],
+            );
+        }
+    });
+    window.add_action(&copy_new_action);
+
+    // Behind the "Copy again" notification button, see app.copy-again
+    let window_weak_for_copy_again = window.downgrade();
+    let clipboard_secret_for_copy_again = clipboard_secret.clone();
+    let pending_copy_for_copy_again = pending_copy.clone();
+    let strings_for_copy_again = strings.clone();
+    let on_copied_for_copy_again = on_copied.clone();
+    let copy_again_action = SimpleAction::new("copy-again", None);
+    copy_again_action.connect_activate(move |_, _| {
+        let text = match clipboard_secret_for_copy_again.borrow().as_ref() {
+            Some(text) => SecretString::from(text.as_str()),
+            None => return,
+        };
+        if let Some(window) = window_weak_for_copy_again.upgrade() {
+            if window_is_active(&window) {
+                copy_to_clipboard(&window, text.as_str());
+                log_clipboard_copy(&strings_for_copy_again.current(), &text);
+                on_copied_for_copy_again(&text);
+                pending_copy_for_copy_again.borrow_mut().take();
+            } else {
+                *pending_copy_for_copy_again.borrow_mut() = Some(text);
+                window.present();
+            }
+        }
+    });
+    window.add_action(&copy_again_action);
+
+    let spin_len_for_regenerate = spin_len.clone();
+    let generate_action_for_regenerate = generate_action.clone();
+    let regenerate_action = SimpleAction::new("regenerate", Some(glib::VariantTy::INT32));
+    regenerate_action.connect_activate(move |_, parameter| {
+        if !generate_action_for_regenerate.is_enabled() {
+            return;
+        }
+        if let Some(groups) = parameter.and_then(|p| p.get::<i32>()) {
+            spin_len_for_regenerate.set_value(groups as f64);
+        }
+        // value-changed only saves the group count, so this is the one generation
+        generate_action_for_regenerate.activate(None);
+    });
+    window.add_action(&regenerate_action);
+
+    let entry_weak_for_keyring = entry.downgrade();
+    let window_weak_for_keyring = window.downgrade();
+    let strings_for_keyring = strings.clone();
+    let show_feedback_for_keyring = show_feedback.clone();
+    let keyring_action = SimpleAction::new("save-keyring", None);
+    keyring_action.connect_activate(move |_, _| {
+        if let (Some(entry), Some(window)) = (
+            entry_weak_for_keyring.upgrade(),
+            window_weak_for_keyring.upgrade(),
+        ) {
+            let password = SecretString::from(entry.text().as_str());
+            if password.as_str().is_empty() {
+                return;
+            }
+            let strings = strings_for_keyring.clone();
+            let show_feedback = show_feedback_for_keyring.clone();
+            let on_saved: Rc<dyn Fn()> = Rc::new(move || show_feedback(strings.current().keyring_saved_label));
+            show_keyring_dialog(&window, Rc::new(strings_for_keyring.current().clone()), password, on_saved);
+        }
+    });
+    window.add_action(&keyring_action);
+
+    let entry_weak_for_pass = entry.downgrade();
+    let window_weak_for_pass = window.downgrade();
+    let strings_for_pass = strings.clone();
+    let show_feedback_for_pass = show_feedback.clone();
+    let pass_action = SimpleAction::new("save-pass", None);
+    pass_action.connect_activate(move |_, _| {
+        if let (Some(entry), Some(window)) = (
+            entry_weak_for_pass.upgrade(),
+            window_weak_for_pass.upgrade(),
+        ) {
+            let password = SecretString::from(entry.text().as_str());
+            if password.as_str().is_empty() {
+                return;
+            }
+            let strings = strings_for_pass.clone();
+            let show_feedback = show_feedback_for_pass.clone();
+            let on_saved: Rc<dyn Fn()> = Rc::new(move || show_feedback(strings.current().pass_saved_label));
+            show_pass_dialog(&window, Rc::new(strings_for_pass.current().clone()), password, on_saved);
+        }
+    });
+    window.add_action(&pass_action);
+
+    let entry_weak_for_kdbx = entry.downgrade();
+    let window_weak_for_kdbx = window.downgrade();
+    let strings_for_kdbx = strings.clone();
+    let settings_for_kdbx = settings.clone();
+    let show_feedback_for_kdbx = show_feedback.clone();
+    let kdbx_action = SimpleAction::new("save-kdbx", None);
+    kdbx_action.connect_activate(move |_, _| {
+        if let (Some(entry), Some(window)) = (
+            entry_weak_for_kdbx.upgrade(),
+            window_weak_for_kdbx.upgrade(),
+        ) {
+            let password = SecretString::from(entry.text().as_str());
+            if password.as_str().is_empty() {
+                return;
+            }
+            let strings = strings_for_kdbx.clone();
+            let show_feedback = show_feedback_for_kdbx.clone();
+            let on_saved: Rc<dyn Fn()> = Rc::new(move || show_feedback(strings.current().kdbx_saved_label));
+            show_kdbx_dialog(
+                &window,
+                Rc::new(strings_for_kdbx.current().clone()),
+                settings_for_kdbx.clone(),
+                password,
+                on_saved,
+            );
+        }
+    });
+    window.add_action(&kdbx_action);
+
+    let window_weak_for_bulk = window.downgrade();
+    let strings_for_bulk = strings.clone();
+    let show_feedback_for_bulk = show_feedback.clone();
+    let spin_len_for_bulk = spin_len.clone();
+    let row_default_strategy_for_bulk = row_default_strategy.clone();
+    let charset_rows_for_bulk = [
+        row_lowercase.clone(),
+        row_uppercase.clone(),
+        row_digits.clone(),
+        row_special.clone(),
+    ];
+    let bulk_action = SimpleAction::new("bulk", None);
+    bulk_action.connect_activate(move |_, _| {
+        if let Some(window) = window_weak_for_bulk.upgrade() {
+            let [lowercase, uppercase, digits, special] = &charset_rows_for_bulk;
+            let options = GenerationOptions::new(
+                lowercase.is_active(),
+                uppercase.is_active(),
+                digits.is_active(),
+                special.is_active(),
+            );
+            let use_default_strategy = row_default_strategy_for_bulk.is_active();
+            if !use_default_strategy && !options.is_valid() {
+                return;
+            }
+            let groups = spin_len_for_bulk.value() as i32;
+            let generate: Rc<dyn Fn() -> SecretString> =
+                Rc::new(move || generate_password(groups, &options, use_default_strategy));
+            show_bulk_dialog(&window, Rc::new(strings_for_bulk.current().clone()), generate, show_feedback_for_bulk.clone());
+        }
+    });
+    window.add_action(&bulk_action);
+
+    let entry_for_qr = entry.clone();
+    let chk_qr_wifi_for_draw = chk_qr_wifi.clone();
+    let entry_qr_ssid_for_draw = entry_qr_ssid.clone();
+    qr_area.set_draw_func(move |_, cr, width, height| {
+        let password = SecretString::from(entry_for_qr.text().as_str());
+        let payload = if chk_qr_wifi_for_draw.is_active() {
+            let ssid = entry_qr_ssid_for_draw.text();
+            if ssid.is_empty() {
+                return;
+            }
+            qr::wifi_payload(&ssid, password.as_str())
+        } else {
+            Zeroizing::new(password.as_str().to_owned())
+        };
+        if let Some(modules) = qr::modules(&payload) {
+            qr::draw(cr, &modules, width, height);
+        }
+    });
+
+    let qr_area_for_toggle = qr_area.clone();
+    btn_qr.connect_toggled(move |btn| {
+        qr_revealer.set_reveal_child(btn.is_active());
+        qr_area_for_toggle.queue_draw();
+    });
+
+    let qr_area_for_entry = qr_area.clone();
+    entry.connect_changed(move |_| qr_area_for_entry.queue_draw());
+
+    let qr_area_for_wifi = qr_area.clone();
+    let entry_qr_ssid_for_wifi = entry_qr_ssid.clone();
+    chk_qr_wifi.connect_toggled(move |chk| {
+        entry_qr_ssid_for_wifi.set_visible(chk.is_active());
+        if chk.is_active() {
+            entry_qr_ssid_for_wifi.grab_focus();
+        }
+        qr_area_for_wifi.queue_draw();
+    });
+
+    let qr_area_for_ssid = qr_area.clone();
+    entry_qr_ssid.connect_changed(move |_| qr_area_for_ssid.queue_draw());
+
+    let update_spelling: Rc<dyn Fn(&I18nStrings)> = {
+        let entry_weak = entry.downgrade();
+        let expander_weak = spelling_expander.downgrade();
+        let label = lbl_spelling.clone();
+        Rc::new(move |strings: &I18nStrings| {
+            if let (Some(entry), Some(expander)) = (entry_weak.upgrade(), expander_weak.upgrade()) {
+                // Only kept while the panel is open, so the spelling does not linger in the widget tree
+                if expander.is_expanded() {
+                    let password = SecretString::from(entry.text().as_str());
+                    label.set_label(&strings.spelled_out(password.as_str()));
+                } else {
+                    label.set_label("");
+                }
+            }
+        })
+    };
+    let update_spelling_for_entry = update_spelling.clone();
+    let strings_for_spelling = strings.clone();
+    entry.connect_changed(move |_| update_spelling_for_entry(&strings_for_spelling.current()));
+    let update_spelling_for_expander = update_spelling.clone();
+    let strings_for_expander = strings.clone();
+    spelling_expander.connect_expanded_notify(move |_| update_spelling_for_expander(&strings_for_expander.current()));
+    strings.bind(&lbl_spelling, move |_, strings| update_spelling(strings));
+
+    let row_colorize_for_entry = row_colorize.clone();
+    entry.connect_changed(move |entry| colorize_password_entry(entry, row_colorize_for_entry.is_active()));
+
+    let entry_for_colorize = entry.clone();
+    let settings_for_colorize = settings.clone();
+    row_colorize.connect_active_notify(move |row| {
+        settings_for_colorize.borrow_mut().colorize_password = row.is_active();
+        save_settings(&settings_for_colorize.borrow());
+        colorize_password_entry(&entry_for_colorize, row.is_active());
+    });
+
+    let entry_weak_for_theme = entry.downgrade();
+    let row_colorize_weak = row_colorize.downgrade();
+    adw::StyleManager::default().connect_dark_notify(move |_| {
+        if let (Some(entry), Some(row_colorize)) = (entry_weak_for_theme.upgrade(), row_colorize_weak.upgrade()) {
+            colorize_password_entry(&entry, row_colorize.is_active());
+        }
+    });
+
+    let refresh_analysis: Rc<dyn Fn(&I18nStrings)> = {
+        let entry_weak = entry.downgrade();
+        let lbl_strength = lbl_strength.clone();
+        let lbl_feedback = lbl_feedback.clone();
+        Rc::new(move |strings: &I18nStrings| {
+            let entry = match entry_weak.upgrade() {
+                Some(entry) => entry,
+                None => return,
+            };
+            let text = SecretString::from(entry.text().as_str());
+            if text.as_str().is_empty() {
+                lbl_strength.set_label("");
+                lbl_feedback.set_visible(false);
+                return;
+            }
+
+            let analysis = strength::analyze(text.as_str());
+            lbl_strength.set_label(&strings.strength_label(analysis.guesses_log2));
+            let feedback = analysis
+                .feedback
+                .iter()
+                .map(|item| strings.feedback_label(*item))
+                .collect::<Vec<_>>()
+                .join("\n");
+            lbl_feedback.set_label(&feedback);
+            lbl_feedback.set_visible(!feedback.is_empty());
+        })
+    };
+
+    let btn_analyze_for_entry = btn_analyze.clone();
+    let strings_for_entry = strings.clone();
+    let refresh_analysis_for_entry = refresh_analysis.clone();
+    entry.connect_changed(move |_| {
+        if btn_analyze_for_entry.is_active() {
+            refresh_analysis_for_entry(&strings_for_entry.current());
+        }
+    });
+
+    // The strength shown for a generated password only needs relabeling,
+    // an analyzed one gets its feedback in the new language as well
+    let btn_analyze_weak_for_strength = btn_analyze.downgrade();
+    let generated_bits_for_strength = generated_bits.clone();
+    strings.bind(&lbl_strength, move |label, strings| {
+        if btn_analyze_weak_for_strength.upgrade().is_some_and(|btn| btn.is_active()) {
+            refresh_analysis(strings);
+        } else if let Some(bits) = generated_bits_for_strength.get() {
+            label.set_label(&strings.strength_label(bits));
+        }
+    });
+
+    let btn_analyze_weak_for_placeholder = btn_analyze.downgrade();
+    strings.bind(&entry, move |entry, strings| {
+        if btn_analyze_weak_for_placeholder.upgrade().is_some_and(|btn| btn.is_active()) {
+            entry.set_placeholder_text(Some(strings.analyze_placeholder));
+        }
+    });
+
+    let entry_for_analyze = entry.clone();
+    let generate_action_for_analyze = generate_action.clone();
+    let spin_len_for_analyze = spin_len.clone();
+    let lbl_strength_for_analyze = lbl_strength.clone();
+    let lbl_feedback_for_analyze = lbl_feedback.clone();
+    let pending_copy_for_analyze = pending_copy.clone();
+    let close_after_copy_armed_for_analyze = close_after_copy_armed.clone();
+    let strings_for_analyze = strings.clone();
+    let update_password_for_analyze = update_password.clone();
+    btn_analyze.connect_toggled(move |btn| {
+        let analyzing = btn.is_active();
+        // The timer holds the countdown while analyzing, and leaving starts
+        // it over with the next password instead of closing on a stale copy
+        pending_copy_for_analyze.borrow_mut().take();
+        close_after_copy_armed_for_analyze.set(false);
+        generate_action_for_analyze.set_enabled(!analyzing);
+        for action in &groups_actions {
+            action.set_enabled(!analyzing);
+        }
+        spin_len_for_analyze.set_sensitive(!analyzing);
+        entry_for_analyze.set_editable(analyzing);
+
+        if analyzing {
+            entry_for_analyze.set_text("");
+            entry_for_analyze.set_placeholder_text(Some(strings_for_analyze.current().analyze_placeholder));
+            lbl_strength_for_analyze.set_label("");
+            entry_for_analyze.grab_focus();
+        } else {
+            entry_for_analyze.set_placeholder_text(None);
+            lbl_feedback_for_analyze.set_visible(false);
+            update_password_for_analyze(spin_len_for_analyze.value() as i32);
+        }
+    });
+
     let settings_for_spin = settings.clone();
     spin_len.connect_value_changed(move |spin| {
         settings_for_spin.borrow_mut().groups = spin.value() as i32;
@@ -840,41 +2623,70 @@ fn build_ui(app: &Application) {
     let remaining_for_auto_close = remaining.clone();
     let runtime_auto_close_flag = runtime_auto_close_active.clone();
     let lbl_timer_weak = lbl_timer.downgrade();
-    chk_auto_close.connect_toggled(move |chk| {
-        let is_active = chk.is_active();
+    row_auto_close.connect_active_notify(move |row| {
+        let is_active = row.is_active();
         settings_for_auto_close.borrow_mut().auto_close = is_active;
         save_settings(&settings_for_auto_close.borrow());
 
         runtime_auto_close_flag.set(is_active);
 
         if is_active {
-            *remaining_for_auto_close.borrow_mut() = CLOSE_AFTER_SEC;
+            *remaining_for_auto_close.borrow_mut() = settings_for_auto_close.borrow().auto_close_sec;
         } else if let Some(lbl_timer) = lbl_timer_weak.upgrade() {
             lbl_timer.set_label("");
         }
     });
 
+    let settings_for_auto_close_sec = settings.clone();
+    let remaining_for_auto_close_sec = remaining.clone();
+    let timer_ring_for_auto_close_sec = timer_ring.clone();
+    row_auto_close_sec.connect_value_notify(move |row| {
+        let seconds = row.value() as i32;
+        settings_for_auto_close_sec.borrow_mut().auto_close_sec = seconds;
+        save_settings(&settings_for_auto_close_sec.borrow());
+        *remaining_for_auto_close_sec.borrow_mut() = seconds;
+        timer_ring_for_auto_close_sec.queue_draw();
+    });
+
+    let settings_for_auto_close_pause = settings.clone();
+    row_auto_close_pause.connect_active_notify(move |row| {
+        settings_for_auto_close_pause.borrow_mut().auto_close_pause = row.is_active();
+        save_settings(&settings_for_auto_close_pause.borrow());
+    });
+
+    let settings_for_close_after_copy = settings.clone();
+    let close_after_copy_armed_for_toggle = close_after_copy_armed.clone();
+    row_close_after_copy.connect_active_notify(move |row| {
+        settings_for_close_after_copy.borrow_mut().close_after_copy = row.is_active();
+        save_settings(&settings_for_close_after_copy.borrow());
+        if !row.is_active() {
+            close_after_copy_armed_for_toggle.set(false);
+        }
+    });
+
     let settings_for_copy_toggle = settings.clone();
     let entry_weak_for_toggle = entry.downgrade();
     let window_weak_for_toggle = window.downgrade();
     let pending_copy_for_toggle = pending_copy.clone();
+    let btn_analyze_for_copy_toggle = btn_analyze.clone();
     let strings_for_copy_toggle = strings.clone();
-    let show_copy_feedback_for_toggle = show_copy_feedback.clone();
-    chk_copy_immediately.connect_toggled(move |chk| {
-        let is_active = chk.is_active();
+    let on_copied_for_toggle = on_copied.clone();
+    row_copy_immediately.connect_active_notify(move |row| {
+        let is_active = row.is_active();
         settings_for_copy_toggle.borrow_mut().copy_immediately = is_active;
         save_settings(&settings_for_copy_toggle.borrow());
 
-        if is_active {
+        // A password typed in for analysis is never copied on its own
+        if is_active && !btn_analyze_for_copy_toggle.is_active() {
             if let (Some(entry), Some(window)) = (
                 entry_weak_for_toggle.upgrade(),
                 window_weak_for_toggle.upgrade(),
             ) {
-                let text = entry.text().to_string();
+                let text = SecretString::from(entry.text().as_str());
                 if window_is_active(&window) {
-                    copy_to_clipboard(&window, &text);
-                    println!("{}", strings_for_copy_toggle.clipboard_log(&text));
-                    show_copy_feedback_for_toggle();
+                    copy_to_clipboard(&window, text.as_str());
+                    log_clipboard_copy(&strings_for_copy_toggle.current(), &text);
+                    on_copied_for_toggle(&text);
                     pending_copy_for_toggle.borrow_mut().take();
                 } else {
                     *pending_copy_for_toggle.borrow_mut() = Some(text);
@@ -886,23 +2698,94 @@ fn build_ui(app: &Application) {
     });
 
     let settings_for_strategy = settings.clone();
-    let chk_lowercase_for_strategy = chk_lowercase.clone();
-    chk_default_strategy.connect_toggled(move |chk| {
-        let is_active = chk.is_active();
+    let row_lowercase_for_strategy = row_lowercase.clone();
+    row_default_strategy.connect_active_notify(move |row| {
+        let is_active = row.is_active();
 
-        if is_active && !chk_lowercase_for_strategy.is_active() {
-            chk_lowercase_for_strategy.set_active(true);
+        if is_active && !row_lowercase_for_strategy.is_active() {
+            row_lowercase_for_strategy.set_active(true);
         }
 
         settings_for_strategy.borrow_mut().default_strategy = is_active;
         save_settings(&settings_for_strategy.borrow());
     });
 
+    let settings_for_hide_toggle = settings.clone();
+    row_hide_on_auto_close.connect_active_notify(move |row| {
+        settings_for_hide_toggle.borrow_mut().hide_on_auto_close = row.is_active();
+        save_settings(&settings_for_hide_toggle.borrow());
+    });
+
+    let settings_for_notifications = settings.clone();
+    row_notifications.connect_active_notify(move |row| {
+        settings_for_notifications.borrow_mut().notifications = row.is_active();
+        save_settings(&settings_for_notifications.borrow());
+    });
+
+    // Applies from the next copy on
+    let settings_for_clear_clipboard = settings.clone();
+    row_clear_clipboard.connect_value_notify(move |row| {
+        settings_for_clear_clipboard.borrow_mut().clear_clipboard_sec = row.value() as u32;
+        save_settings(&settings_for_clear_clipboard.borrow());
+    });
+
+    // Held while the window is hidden in the background, so the next
+    // activation skips startup and just shows the window again
+    let background_hold = Rc::new(RefCell::new(None::<gio::ApplicationHoldGuard>));
+
+    let hide_to_background: Rc<dyn Fn()> = {
+        let window_weak = window.downgrade();
+        let entry = entry.clone();
+        let btn_analyze = btn_analyze.clone();
+        let pending_copy = pending_copy.clone();
+        let background_hold = background_hold.clone();
+        Rc::new(move || {
+            if let Some(window) = window_weak.upgrade() {
+                // Leaving analyze mode generates a password, so do it before wiping
+                btn_analyze.set_active(false);
+                entry.set_text("");
+                pending_copy.borrow_mut().take();
+                if let Some(app) = window.application() {
+                    background_hold.replace(Some(app.hold()));
+                }
+                window.set_visible(false);
+                glib::g_info!(LOG_DOMAIN, "Window hidden to the background");
+            }
+        })
+    };
+
+    let background_hold_for_show = background_hold.clone();
+    let settings_for_show = settings.clone();
+    let row_auto_close_for_show = row_auto_close.clone();
+    let runtime_auto_close_active_for_show = runtime_auto_close_active.clone();
+    let close_after_copy_armed_for_show = close_after_copy_armed.clone();
+    let remaining_for_show = remaining.clone();
+    let spin_len_for_show = spin_len.clone();
+    let update_password_for_show = update_password.clone();
+    window.connect_visible_notify(move |window| {
+        if !window.is_visible() || background_hold_for_show.borrow_mut().take().is_none() {
+            return;
+        }
+        // Back from the background: start over like a fresh launch
+        runtime_auto_close_active_for_show.set(row_auto_close_for_show.is_active());
+        close_after_copy_armed_for_show.set(false);
+        *remaining_for_show.borrow_mut() = settings_for_show.borrow().auto_close_sec;
+        update_password_for_show(spin_len_for_show.value() as i32);
+    });
+
     let window_weak = window.downgrade();
     let lbl_timer_weak = lbl_timer.downgrade();
-    let chk_auto_close_weak = chk_auto_close.downgrade();
+    let timer_ring_weak = timer_ring.downgrade();
+    let background_hold_for_timer = background_hold.clone();
+    let row_auto_close_weak = row_auto_close.downgrade();
+    let btn_qr_weak = btn_qr.downgrade();
+    let btn_analyze_weak = btn_analyze.downgrade();
+    let preferences_window_weak = preferences_window.downgrade();
+    let settings_for_timer = settings.clone();
     let remaining = remaining.clone();
     let runtime_auto_close_active = runtime_auto_close_active.clone();
+    let close_after_copy_armed = close_after_copy_armed.clone();
+    let hovered = hovered.clone();
     let strings_for_timer = strings.clone();
 
     glib::timeout_add_seconds_local(1, move || {
@@ -914,25 +2797,56 @@ fn build_ui(app: &Application) {
             Some(l) => l,
             None => return glib::ControlFlow::Break,
         };
-        let chk_auto_close = match chk_auto_close_weak.upgrade() {
+        let timer_ring = match timer_ring_weak.upgrade() {
+            Some(t) => t,
+            None => return glib::ControlFlow::Break,
+        };
+        let row_auto_close = match row_auto_close_weak.upgrade() {
             Some(c) => c,
             None => return glib::ControlFlow::Break,
         };
 
         if !window.is_visible() {
+            // Keep ticking while hidden in the background, the window comes back later
+            if background_hold_for_timer.borrow().is_some() {
+                return glib::ControlFlow::Continue;
+            }
             return glib::ControlFlow::Break;
         }
 
-        if !chk_auto_close.is_active() || !runtime_auto_close_active.get() {
+        // Never close while the user is typing a password to analyze
+        let analyzing = btn_analyze_weak.upgrade().is_some_and(|btn| btn.is_active());
+        let auto_closing = row_auto_close.is_active() && runtime_auto_close_active.get();
+        if analyzing || (!auto_closing && !close_after_copy_armed.get()) {
             lbl_timer.set_label("");
+            timer_ring.set_visible(false);
             return glib::ControlFlow::Continue;
         }
 
+        let paused = settings_for_timer.borrow().auto_close_pause
+            && (hovered.get()
+                || window_is_active(&window)
+                || preferences_window_weak.upgrade().is_some_and(|w| w.is_visible()));
+
         let mut r = remaining.borrow_mut();
-        *r -= 1;
-        lbl_timer.set_label(&strings_for_timer.timer_label(*r));
+        if !paused {
+            *r -= 1;
+        }
+        lbl_timer.set_label(&strings_for_timer.current().timer_label(*r));
+        timer_ring.set_visible(true);
+        timer_ring.queue_draw();
 
         if *r <= 0 {
+            if let Some(btn_qr) = btn_qr_weak.upgrade() {
+                btn_qr.set_active(false);
+            }
+            if settings_for_timer.borrow().hide_on_auto_close {
+                drop(r);
+                lbl_timer.set_label("");
+                timer_ring.set_visible(false);
+                hide_to_background();
+                return glib::ControlFlow::Continue;
+            }
             window.close();
             return glib::ControlFlow::Break;
         }
@@ -940,22 +2854,37 @@ fn build_ui(app: &Application) {
         glib::ControlFlow::Continue
     });
 
+    // Wipe the displayed password when the window goes away, including via auto-close
+    let entry_for_close = entry.clone();
+    let pending_copy_for_close = pending_copy.clone();
+    let clipboard_secret_for_close = clipboard_secret.clone();
+    window.connect_close_request(move |_| {
+        entry_for_close.set_text("");
+        pending_copy_for_close.borrow_mut().take();
+        clipboard_secret_for_close.borrow_mut().take();
+        glib::Propagation::Proceed
+    });
+
     // Register is-active handler BEFORE present() to catch focus changes
     let pending_copy_for_notify = pending_copy.clone();
     let strings_for_notify = strings.clone();
-    let show_copy_feedback_for_notify = show_copy_feedback.clone();
+    let on_copied_for_notify = on_copied.clone();
+    let clear_due_clipboard_for_notify = clear_due_clipboard.clone();
     window.connect_notify_local(Some("is-active"), move |win: &ApplicationWindow, _| {
         if window_is_active(win) {
             if let Some(text) = pending_copy_for_notify.borrow_mut().take() {
                 // Defer clipboard write to allow Wayland to fully process focus
                 let win = win.clone();
                 let strings = strings_for_notify.clone();
-                let show_feedback = show_copy_feedback_for_notify.clone();
+                let on_copied = on_copied_for_notify.clone();
                 glib::timeout_add_local_once(Duration::from_millis(50), move || {
-                    copy_to_clipboard(&win, &text);
-                    println!("{}", strings.clipboard_log(&text));
-                    show_feedback();
+                    copy_to_clipboard(&win, text.as_str());
+                    log_clipboard_copy(&strings.current(), &text);
+                    on_copied(&text);
                 });
+            } else {
+                let clear_due_clipboard = clear_due_clipboard_for_notify.clone();
+                glib::timeout_add_local_once(Duration::from_millis(50), move || clear_due_clipboard());
             }
         }
     });
@@ -973,18 +2902,18 @@ fn build_ui(app: &Application) {
     let pending_copy_for_fallback = pending_copy.clone();
     let window_for_fallback = window.clone();
     let strings_for_fallback = strings.clone();
-    let show_copy_feedback_for_fallback = show_copy_feedback.clone();
+    let on_copied_for_fallback = on_copied.clone();
     glib::timeout_add_local_once(Duration::from_millis(250), move || {
         if let Some(text) = pending_copy_for_fallback.borrow_mut().take() {
             if window_is_active(&window_for_fallback) {
                 // Defer clipboard write to allow Wayland to fully process focus
                 let win = window_for_fallback.clone();
                 let strings = strings_for_fallback.clone();
-                let show_feedback = show_copy_feedback_for_fallback.clone();
+                let on_copied = on_copied_for_fallback.clone();
                 glib::timeout_add_local_once(Duration::from_millis(50), move || {
-                    copy_to_clipboard(&win, &text);
-                    println!("{}", strings.clipboard_log(&text));
-                    show_feedback();
+                    copy_to_clipboard(&win, text.as_str());
+                    log_clipboard_copy(&strings.current(), &text);
+                    on_copied(&text);
                 });
             } else {
                 // Window still not active - restore for notification handler
@@ -994,15 +2923,105 @@ fn build_ui(app: &Application) {
     });
 }
 
-fn generate_password(groups: i32, options: &GenerationOptions, use_default_strategy: bool) -> String {
+fn main_menu(strings: &I18nStrings) -> gio::Menu {
+    let save_section = gio::Menu::new();
+    save_section.append(Some(strings.keyring_button), Some("win.save-keyring"));
+    save_section.append(Some(strings.pass_button), Some("win.save-pass"));
+    save_section.append(Some(strings.kdbx_button), Some("win.save-kdbx"));
+    save_section.append(Some(strings.bulk_button), Some("win.bulk"));
+    let app_section = gio::Menu::new();
+    app_section.append(Some(strings.preferences_title), Some("win.preferences"));
+    app_section.append(Some(strings.shortcuts_title), Some("win.show-help-overlay"));
+    let main_menu = gio::Menu::new();
+    main_menu.append_section(None, &save_section);
+    main_menu.append_section(None, &app_section);
+    main_menu
+}
+
+/// The shortcuts window layout: group titles with their actions and labels.
+fn shortcut_groups(strings: &I18nStrings) -> [(&'static str, Vec<(&'static str, &'static str)>); 3] {
+    [
+        (
+            strings.shortcuts_password_group,
+            vec![
+                ("win.generate", strings.shortcut_generate),
+                ("win.copy", strings.shortcut_copy),
+                ("win.autotype", strings.shortcut_autotype),
+                ("win.more-groups", strings.shortcut_more_groups),
+                ("win.fewer-groups", strings.shortcut_fewer_groups),
+            ],
+        ),
+        (
+            strings.charset_section_label,
+            vec![
+                ("win.lowercase", strings.lowercase_label),
+                ("win.uppercase", strings.uppercase_label),
+                ("win.digits", strings.digits_label),
+                ("win.special", strings.special_label),
+            ],
+        ),
+        (
+            strings.shortcuts_general_group,
+            vec![
+                ("win.auto-close", strings.auto_close_label),
+                ("win.preferences", strings.preferences_title),
+                ("win.show-help-overlay", strings.shortcut_show_shortcuts),
+                ("win.close", strings.shortcut_close),
+                ("app.quit", strings.shortcut_quit),
+            ],
+        ),
+    ]
+}
+
+fn shortcuts_window(strings: &I18nStrings) -> gtk::ShortcutsWindow {
+    // Sections and groups can only be added from UI definitions before GTK 4.14
+    let mut groups = String::new();
+    for (group_title, shortcuts) in shortcut_groups(strings) {
+        groups.push_str(&format!(
+            "<child><object class=\"GtkShortcutsGroup\"><property name=\"title\">{}</property>",
+            glib::markup_escape_text(group_title)
+        ));
+        for (action, title) in shortcuts {
+            let accelerator = SHORTCUTS
+                .iter()
+                .find(|(name, _)| *name == action)
+                .map(|(_, accels)| accels[0])
+                .unwrap_or_default();
+            groups.push_str(&format!(
+                "<child><object class=\"GtkShortcutsShortcut\">\
+                 <property name=\"title\">{}</property>\
+                 <property name=\"accelerator\">{}</property>\
+                 </object></child>",
+                glib::markup_escape_text(title),
+                glib::markup_escape_text(accelerator)
+            ));
+        }
+        groups.push_str("</object></child>");
+    }
+    let ui = format!(
+        "<interface><object class=\"GtkShortcutsWindow\" id=\"shortcuts\">\
+         <property name=\"modal\">1</property>\
+         <property name=\"title\">{}</property>\
+         <child><object class=\"GtkShortcutsSection\">\
+         <property name=\"section-name\">shortcuts</property>{}</object></child>\
+         </object></interface>",
+        glib::markup_escape_text(strings.shortcuts_title),
+        groups
+    );
+    gtk::Builder::from_string(&ui)
+        .object("shortcuts")
+        .expect("UI definition declares the shortcuts window")
+}
+
+fn generate_password(groups: i32, options: &GenerationOptions, use_default_strategy: bool) -> SecretString {
     let mut rng = rand::thread_rng();
     let total_groups = groups.max(1);
     let total_chars = (total_groups * 5) as usize;
 
-    let mut password_chars: Vec<u8>;
+    let mut password_chars: Zeroizing<Vec<u8>>;
 
     if use_default_strategy {
-        password_chars = vec![0u8; total_chars];
+        password_chars = Zeroizing::new(vec![0u8; total_chars]);
         for ch in password_chars.iter_mut() {
             let idx = rng.gen_range(0..LOWER.len());
             *ch = LOWER[idx];
@@ -1020,7 +3039,7 @@ fn generate_password(groups: i32, options: &GenerationOptions, use_default_strat
         }
 
         if forced_pools.len() > total_chars {
-            return String::new();
+            return SecretString::default();
         }
 
         let mut positions: Vec<usize> = (0..total_chars).collect();
@@ -1035,21 +3054,812 @@ fn generate_password(groups: i32, options: &GenerationOptions, use_default_strat
     } else {
         let pool = options.pool();
         if pool.is_empty() {
-            return String::new();
+            return SecretString::default();
         }
 
-        password_chars = Vec::with_capacity(total_chars);
+        password_chars = Zeroizing::new(Vec::with_capacity(total_chars));
         for _ in 0..total_chars {
             let idx = rng.gen_range(0..pool.len());
             password_chars.push(pool[idx]);
         }
     }
 
-    password_chars
-        .chunks(5)
-        .map(|chunk| chunk.iter().map(|&c| c as char).collect::<String>())
-        .collect::<Vec<String>>()
-        .join("-")
+    // Sized up front so the buffer never reallocates and leaves unwiped copies behind
+    let mut password = String::with_capacity(total_chars + total_groups as usize);
+    for (i, chunk) in password_chars.chunks(5).enumerate() {
+        if i > 0 {
+            password.push('-');
+        }
+        password.extend(chunk.iter().map(|&c| c as char));
+    }
+    SecretString::new(password)
+}
+
+/// A numeric PIN of `digits` digits, without group separators.
+fn generate_pin(digits: usize) -> SecretString {
+    let mut rng = rand::thread_rng();
+    let mut pin = String::with_capacity(digits);
+    for _ in 0..digits {
+        pin.push(DIGITS[rng.gen_range(0..DIGITS.len())] as char);
+    }
+    SecretString::new(pin)
+}
+
+/// A passphrase of `count` words from `WORDLIST`, joined by dashes.
+fn generate_passphrase(count: usize) -> SecretString {
+    let words: Vec<&str> = WORDLIST.lines().collect();
+    let mut rng = rand::thread_rng();
+    let capacity = words.iter().map(|word| word.len()).max().unwrap_or(0) * count + count;
+    let mut passphrase = String::with_capacity(capacity);
+    for i in 0..count {
+        if i > 0 {
+            passphrase.push('-');
+        }
+        passphrase.push_str(words[rng.gen_range(0..words.len())]);
+    }
+    SecretString::new(passphrase)
+}
+
+fn show_keyring_dialog(
+    parent: &ApplicationWindow,
+    strings: Rc<I18nStrings>,
+    password: SecretString,
+    on_saved: Rc<dyn Fn()>,
+) {
+    let dialog = adw::Window::builder()
+        .title(strings.keyring_button)
+        .transient_for(parent)
+        .modal(true)
+        .destroy_with_parent(true)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(20)
+        .margin_bottom(20)
+        .margin_start(20)
+        .margin_end(20)
+        .build();
+    dialog.set_content(Some(&with_header_bar(&content)));
+
+    let grid = gtk::Grid::builder()
+        .row_spacing(8)
+        .column_spacing(12)
+        .build();
+    content.append(&grid);
+
+    let titles = [
+        strings.keyring_label_field,
+        strings.keyring_username_field,
+        strings.keyring_url_field,
+    ];
+    let fields: Vec<Entry> = titles
+        .into_iter()
+        .enumerate()
+        .map(|(row, title)| {
+            let label = Label::new(Some(title));
+            label.set_halign(gtk::Align::Start);
+            let field = Entry::builder()
+                .hexpand(true)
+                .activates_default(true)
+                .build();
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(&field, 1, row as i32, 1, 1);
+            field
+        })
+        .collect();
+
+    let lbl_error = Label::builder()
+        .wrap(true)
+        .visible(false)
+        .build();
+    lbl_error.add_css_class("error");
+    content.append(&lbl_error);
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .halign(gtk::Align::End)
+        .build();
+    content.append(&buttons);
+
+    let btn_cancel = Button::with_label(strings.cancel_button);
+    buttons.append(&btn_cancel);
+    let btn_save = Button::with_label(strings.save_button);
+    btn_save.add_css_class("suggested-action");
+    btn_save.set_sensitive(false);
+    buttons.append(&btn_save);
+    dialog.set_default_widget(Some(&btn_save));
+
+    let btn_save_for_label = btn_save.clone();
+    fields[0].connect_changed(move |field| {
+        btn_save_for_label.set_sensitive(!field.text().is_empty());
+    });
+
+    let dialog_weak = dialog.downgrade();
+    btn_cancel.connect_clicked(move |_| {
+        if let Some(dialog) = dialog_weak.upgrade() {
+            dialog.close();
+        }
+    });
+
+    let password = Rc::new(password);
+    let dialog_weak = dialog.downgrade();
+    btn_save.connect_clicked(move |btn| {
+        let entry = KeyringEntry {
+            label: fields[0].text().to_string(),
+            username: fields[1].text().to_string(),
+            url: fields[2].text().to_string(),
+        };
+        btn.set_sensitive(false);
+
+        let btn = btn.clone();
+        let dialog_weak = dialog_weak.clone();
+        let lbl_error = lbl_error.clone();
+        let password = password.clone();
+        let strings = strings.clone();
+        let on_saved = on_saved.clone();
+        glib::spawn_future_local(async move {
+            let result = match SecretService::session().await {
+                Ok(service) => service.store(&entry, &password, "").await,
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(()) => {
+                    glib::g_info!(LOG_DOMAIN, "Password saved to keyring");
+                    if let Some(dialog) = dialog_weak.upgrade() {
+                        dialog.close();
+                    }
+                    on_saved();
+                }
+                Err(err) => {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to save to keyring: {}", err);
+                    lbl_error.set_label(&strings.save_error_label(err.message()));
+                    lbl_error.set_visible(true);
+                    btn.set_sensitive(true);
+                }
+            }
+        });
+    });
+
+    dialog.present();
+}
+
+fn show_pass_dialog(
+    parent: &ApplicationWindow,
+    strings: Rc<I18nStrings>,
+    password: SecretString,
+    on_saved: Rc<dyn Fn()>,
+) {
+    let dialog = adw::Window::builder()
+        .title(strings.pass_button)
+        .transient_for(parent)
+        .modal(true)
+        .destroy_with_parent(true)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(20)
+        .margin_bottom(20)
+        .margin_start(20)
+        .margin_end(20)
+        .build();
+    dialog.set_content(Some(&with_header_bar(&content)));
+
+    let grid = gtk::Grid::builder()
+        .row_spacing(8)
+        .column_spacing(12)
+        .build();
+    content.append(&grid);
+
+    let folders = PasswordStore::open_default().folders();
+    let folder_list = gtk::StringList::new(&[strings.pass_root_folder]);
+    for folder in &folders {
+        folder_list.append(folder);
+    }
+    let drop_folder = gtk::DropDown::builder()
+        .model(&folder_list)
+        .hexpand(true)
+        .build();
+    let lbl_folder = Label::new(Some(strings.pass_folder_field));
+    lbl_folder.set_halign(gtk::Align::Start);
+    grid.attach(&lbl_folder, 0, 0, 1, 1);
+    grid.attach(&drop_folder, 1, 0, 1, 1);
+
+    let field_name = Entry::builder()
+        .hexpand(true)
+        .activates_default(true)
+        .build();
+    let lbl_name = Label::new(Some(strings.pass_name_field));
+    lbl_name.set_halign(gtk::Align::Start);
+    grid.attach(&lbl_name, 0, 1, 1, 1);
+    grid.attach(&field_name, 1, 1, 1, 1);
+
+    let chk_overwrite = CheckButton::with_label(strings.pass_overwrite_label);
+    content.append(&chk_overwrite);
+
+    let lbl_error = Label::builder()
+        .wrap(true)
+        .visible(false)
+        .build();
+    lbl_error.add_css_class("error");
+    content.append(&lbl_error);
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .halign(gtk::Align::End)
+        .build();
+    content.append(&buttons);
+
+    let btn_cancel = Button::with_label(strings.cancel_button);
+    buttons.append(&btn_cancel);
+    let btn_save = Button::with_label(strings.save_button);
+    btn_save.add_css_class("suggested-action");
+    btn_save.set_sensitive(false);
+    buttons.append(&btn_save);
+    dialog.set_default_widget(Some(&btn_save));
+
+    let btn_save_for_name = btn_save.clone();
+    field_name.connect_changed(move |field| {
+        btn_save_for_name.set_sensitive(!field.text().trim().is_empty());
+    });
+
+    let dialog_weak = dialog.downgrade();
+    btn_cancel.connect_clicked(move |_| {
+        if let Some(dialog) = dialog_weak.upgrade() {
+            dialog.close();
+        }
+    });
+
+    let dialog_weak = dialog.downgrade();
+    btn_save.connect_clicked(move |btn| {
+        let name = match drop_folder.selected() {
+            0 | gtk::INVALID_LIST_POSITION => field_name.text().to_string(),
+            index => format!("{}/{}", folders[index as usize - 1], field_name.text()),
+        };
+        let overwrite = chk_overwrite.is_active();
+        let password = SecretString::from(password.as_str());
+        btn.set_sensitive(false);
+
+        let btn = btn.clone();
+        let dialog_weak = dialog_weak.clone();
+        let lbl_error = lbl_error.clone();
+        let strings = strings.clone();
+        let on_saved = on_saved.clone();
+        glib::spawn_future_local(async move {
+            let result = gio::spawn_blocking(move || PasswordStore::open_default().insert(&name, &password, overwrite))
+                .await
+                .unwrap_or_else(|_| Err(std::io::Error::other("pass insert panicked")));
+            match result {
+                Ok(_) => {
+                    if let Some(dialog) = dialog_weak.upgrade() {
+                        dialog.close();
+                    }
+                    on_saved();
+                }
+                Err(err) => {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to insert into pass: {}", err);
+                    lbl_error.set_label(&strings.pass_error_label(&err.to_string()));
+                    lbl_error.set_visible(true);
+                    btn.set_sensitive(true);
+                }
+            }
+        });
+    });
+
+    dialog.present();
+}
+
+fn show_kdbx_dialog(
+    parent: &ApplicationWindow,
+    strings: Rc<I18nStrings>,
+    settings: Rc<RefCell<AppSettings>>,
+    password: SecretString,
+    on_saved: Rc<dyn Fn()>,
+) {
+    let dialog = adw::Window::builder()
+        .title(strings.kdbx_button)
+        .transient_for(parent)
+        .modal(true)
+        .destroy_with_parent(true)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(20)
+        .margin_bottom(20)
+        .margin_start(20)
+        .margin_end(20)
+        .build();
+    dialog.set_content(Some(&with_header_bar(&content)));
+
+    let grid = gtk::Grid::builder()
+        .row_spacing(8)
+        .column_spacing(12)
+        .build();
+    content.append(&grid);
+    let attach_row = |row: i32, title: &str, widget: &gtk::Widget| {
+        let label = Label::new(Some(title));
+        label.set_halign(gtk::Align::Start);
+        grid.attach(&label, 0, row, 1, 1);
+        grid.attach(widget, 1, row, 1, 1);
+    };
+
+    let btn_save = Button::with_label(strings.save_button);
+    let fld_title = Entry::builder()
+        .hexpand(true)
+        .activates_default(true)
+        .build();
+    let database = Rc::new(RefCell::new(settings.borrow().kdbx_database.clone()));
+    let key_file = Rc::new(RefCell::new(settings.borrow().kdbx_key_file.clone()));
+
+    let update_save_sensitivity: Rc<dyn Fn()> = {
+        let btn_save = btn_save.clone();
+        let fld_title = fld_title.clone();
+        let database = database.clone();
+        Rc::new(move || {
+            btn_save.set_sensitive(database.borrow().is_some() && !fld_title.text().is_empty());
+        })
+    };
+
+    let btn_database = file_picker_button(&dialog, &strings, database.clone(), update_save_sensitivity.clone());
+    attach_row(0, strings.kdbx_database_field, btn_database.upcast_ref());
+    let btn_key_file = file_picker_button(&dialog, &strings, key_file.clone(), update_save_sensitivity.clone());
+    let btn_clear_key_file = Button::from_icon_name("edit-clear-symbolic");
+    let key_file_box = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(6)
+        .build();
+    key_file_box.append(&btn_key_file);
+    key_file_box.append(&btn_clear_key_file);
+    attach_row(1, strings.kdbx_key_file_field, key_file_box.upcast_ref());
+
+    let key_file_for_clear = key_file.clone();
+    let strings_for_clear = strings.clone();
+    btn_clear_key_file.connect_clicked(move |_| {
+        key_file_for_clear.borrow_mut().take();
+        btn_key_file.set_label(strings_for_clear.kdbx_choose_file);
+    });
+
+    let fld_master = gtk::PasswordEntry::builder()
+        .hexpand(true)
+        .show_peek_icon(true)
+        .activates_default(true)
+        .build();
+    attach_row(2, strings.kdbx_master_password_field, fld_master.upcast_ref());
+    attach_row(3, strings.kdbx_title_field, fld_title.upcast_ref());
+
+    let fld_username = Entry::builder()
+        .hexpand(true)
+        .activates_default(true)
+        .build();
+    attach_row(4, strings.keyring_username_field, fld_username.upcast_ref());
+    let fld_url = Entry::builder()
+        .hexpand(true)
+        .activates_default(true)
+        .build();
+    attach_row(5, strings.keyring_url_field, fld_url.upcast_ref());
+
+    let fld_notes = gtk::TextView::builder()
+        .wrap_mode(gtk::WrapMode::WordChar)
+        .build();
+    let notes_scroller = gtk::ScrolledWindow::builder()
+        .child(&fld_notes)
+        .min_content_height(60)
+        .has_frame(true)
+        .build();
+    attach_row(6, strings.kdbx_notes_field, notes_scroller.upcast_ref());
+
+    let lbl_error = Label::builder()
+        .wrap(true)
+        .visible(false)
+        .build();
+    lbl_error.add_css_class("error");
+    content.append(&lbl_error);
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .halign(gtk::Align::End)
+        .build();
+    content.append(&buttons);
+
+    let btn_cancel = Button::with_label(strings.cancel_button);
+    buttons.append(&btn_cancel);
+    btn_save.add_css_class("suggested-action");
+    buttons.append(&btn_save);
+    dialog.set_default_widget(Some(&btn_save));
+    update_save_sensitivity();
+
+    let update_save_for_title = update_save_sensitivity.clone();
+    fld_title.connect_changed(move |_| update_save_for_title());
+
+    let dialog_weak = dialog.downgrade();
+    btn_cancel.connect_clicked(move |_| {
+        if let Some(dialog) = dialog_weak.upgrade() {
+            dialog.close();
+        }
+    });
+
+    let dialog_weak = dialog.downgrade();
+    btn_save.connect_clicked(move |btn| {
+        let path = match database.borrow().clone() {
+            Some(path) => path,
+            None => return,
+        };
+        let key_path = key_file.borrow().clone();
+        let master = SecretString::from(fld_master.text().as_str());
+        let buffer = fld_notes.buffer();
+        let entry = KdbxEntry {
+            title: fld_title.text().to_string(),
+            username: fld_username.text().to_string(),
+            url: fld_url.text().to_string(),
+            notes: buffer.text(&buffer.start_iter(), &buffer.end_iter(), false).to_string(),
+        };
+        let password = SecretString::from(password.as_str());
+        btn.set_sensitive(false);
+
+        let btn = btn.clone();
+        let dialog_weak = dialog_weak.clone();
+        let lbl_error = lbl_error.clone();
+        let strings = strings.clone();
+        let settings = settings.clone();
+        let on_saved = on_saved.clone();
+        glib::spawn_future_local(async move {
+            let database_path = path.clone();
+            let key_file_path = key_path.clone();
+            // Unlocking runs the database's KDF, which is deliberately slow
+            let result = gio::spawn_blocking(move || {
+                let key = KdbxKey {
+                    password: if master.as_str().is_empty() { None } else { Some(&master) },
+                    key_file: key_file_path.as_deref(),
+                };
+                kdbx::append_entry(&database_path, &key, &entry, &password)
+            })
+            .await
+            .unwrap_or_else(|_| Err(std::io::Error::other("KeePass export panicked")));
+            match result {
+                Ok(()) => {
+                    glib::g_info!(LOG_DOMAIN, "Password added to KeePass database");
+                    {
+                        let mut settings = settings.borrow_mut();
+                        settings.kdbx_database = Some(path);
+                        settings.kdbx_key_file = key_path;
+                        save_settings(&settings);
+                    }
+                    if let Some(dialog) = dialog_weak.upgrade() {
+                        dialog.close();
+                    }
+                    on_saved();
+                }
+                Err(err) => {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to add to KeePass database: {}", err);
+                    lbl_error.set_label(&strings.save_error_label(&err.to_string()));
+                    lbl_error.set_visible(true);
+                    btn.set_sensitive(true);
+                }
+            }
+        });
+    });
+
+    dialog.present();
+}
+
+fn show_bulk_dialog(
+    parent: &ApplicationWindow,
+    strings: Rc<I18nStrings>,
+    generate: Rc<dyn Fn() -> SecretString>,
+    on_exported: Rc<dyn Fn(&str)>,
+) {
+    let dialog = adw::Window::builder()
+        .title(strings.bulk_button)
+        .transient_for(parent)
+        .modal(true)
+        .destroy_with_parent(true)
+        .resizable(false)
+        .build();
+
+    let content = gtk::Box::builder()
+        .orientation(Orientation::Vertical)
+        .spacing(12)
+        .margin_top(20)
+        .margin_bottom(20)
+        .margin_start(20)
+        .margin_end(20)
+        .build();
+    dialog.set_content(Some(&with_header_bar(&content)));
+
+    let grid = gtk::Grid::builder()
+        .row_spacing(8)
+        .column_spacing(12)
+        .build();
+    content.append(&grid);
+
+    let spin_count = SpinButton::with_range(1.0, 1000.0, 1.0);
+    spin_count.set_value(50.0);
+    let lbl_count = Label::new(Some(strings.bulk_count_field));
+    lbl_count.set_halign(gtk::Align::Start);
+    grid.attach(&lbl_count, 0, 0, 1, 1);
+    grid.attach(&spin_count, 1, 0, 1, 1);
+
+    let format_labels: Vec<&str> = ExportFormat::ALL.iter().map(|format| format.label()).collect();
+    let drop_format = gtk::DropDown::from_strings(&format_labels);
+    drop_format.set_hexpand(true);
+    let lbl_format = Label::new(Some(strings.bulk_format_field));
+    lbl_format.set_halign(gtk::Align::Start);
+    grid.attach(&lbl_format, 0, 1, 1, 1);
+    grid.attach(&drop_format, 1, 1, 1, 1);
+
+    let lbl_usernames = Label::new(Some(strings.bulk_usernames_field));
+    lbl_usernames.set_halign(gtk::Align::Start);
+    content.append(&lbl_usernames);
+    let txt_usernames = gtk::TextView::new();
+    let usernames_scroller = gtk::ScrolledWindow::builder()
+        .child(&txt_usernames)
+        .min_content_height(120)
+        .has_frame(true)
+        .build();
+    content.append(&usernames_scroller);
+
+    let lbl_warning = Label::builder()
+        .label(format!("⚠️ {}", strings.bulk_warning_label))
+        .wrap(true)
+        .max_width_chars(40)
+        .xalign(0.0)
+        .build();
+    lbl_warning.add_css_class("breach-warning");
+    content.append(&lbl_warning);
+
+    let lbl_error = Label::builder()
+        .wrap(true)
+        .visible(false)
+        .build();
+    lbl_error.add_css_class("error");
+    content.append(&lbl_error);
+
+    let buttons = gtk::Box::builder()
+        .orientation(Orientation::Horizontal)
+        .spacing(10)
+        .halign(gtk::Align::End)
+        .build();
+    content.append(&buttons);
+
+    let btn_cancel = Button::with_label(strings.cancel_button);
+    buttons.append(&btn_cancel);
+    let btn_export = Button::with_label(strings.export_button);
+    btn_export.add_css_class("suggested-action");
+    buttons.append(&btn_export);
+
+    let dialog_weak = dialog.downgrade();
+    btn_cancel.connect_clicked(move |_| {
+        if let Some(dialog) = dialog_weak.upgrade() {
+            dialog.close();
+        }
+    });
+
+    let dialog_weak = dialog.downgrade();
+    btn_export.connect_clicked(move |_| {
+        let dialog = match dialog_weak.upgrade() {
+            Some(dialog) => dialog,
+            None => return,
+        };
+        let format = ExportFormat::ALL[(drop_format.selected() as usize).min(ExportFormat::ALL.len() - 1)];
+        let buffer = txt_usernames.buffer();
+        let usernames = bulk::parse_usernames(&buffer.text(&buffer.start_iter(), &buffer.end_iter(), false));
+        let count = spin_count.value() as usize;
+
+        let file_dialog = gtk::FileDialog::builder()
+            .title(strings.export_button)
+            .initial_name(format!("passwords.{}", format.extension()))
+            .modal(true)
+            .build();
+        let generate = generate.clone();
+        let lbl_error = lbl_error.clone();
+        let strings = strings.clone();
+        let on_exported = on_exported.clone();
+        let dialog_weak = dialog.downgrade();
+        file_dialog.save(Some(&dialog), None::<&gio::Cancellable>, move |result| {
+            // An error here means the dialog was dismissed
+            let path = match result.ok().and_then(|file| file.path()) {
+                Some(path) => path,
+                None => return,
+            };
+            let items = bulk::generate_batch(count, &usernames, &*generate);
+            match bulk::write_private(&path, &bulk::render(format, &items)) {
+                Ok(()) => {
+                    glib::g_info!(LOG_DOMAIN, "Exported {} passwords", items.len());
+                    if let Some(dialog) = dialog_weak.upgrade() {
+                        dialog.close();
+                    }
+                    on_exported(&strings.bulk_exported_label(items.len()));
+                }
+                Err(err) => {
+                    glib::g_warning!(LOG_DOMAIN, "Failed to export passwords: {}", err);
+                    lbl_error.set_label(&strings.save_error_label(&err.to_string()));
+                    lbl_error.set_visible(true);
+                }
+            }
+        });
+    });
+
+    dialog.present();
+}
+
+/// Puts `content` below a header bar, which gives dialogs their title and
+/// close button.
+fn with_header_bar(content: &impl IsA<gtk::Widget>) -> adw::ToolbarView {
+    let toolbar_view = adw::ToolbarView::new();
+    toolbar_view.add_top_bar(&adw::HeaderBar::new());
+    toolbar_view.set_content(Some(content));
+    toolbar_view
+}
+
+/// A button that shows the chosen file's name and opens a file dialog when clicked.
+fn file_picker_button(
+    parent: &adw::Window,
+    strings: &I18nStrings,
+    selection: Rc<RefCell<Option<PathBuf>>>,
+    on_changed: Rc<dyn Fn()>,
+) -> Button {
+    let file_label = |path: &Option<PathBuf>, placeholder: &str| {
+        path.as_ref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| placeholder.to_owned())
+    };
+
+    let placeholder = strings.kdbx_choose_file;
+    let button = Button::with_label(&file_label(&selection.borrow(), placeholder));
+    button.set_hexpand(true);
+    let parent_weak = parent.downgrade();
+    button.connect_clicked(move |button| {
+        if let Some(parent) = parent_weak.upgrade() {
+            let dialog = gtk::FileDialog::builder()
+                .title(placeholder)
+                .modal(true)
+                .build();
+            let button = button.clone();
+            let selection = selection.clone();
+            let on_changed = on_changed.clone();
+            dialog.open(Some(&parent), None::<&gio::Cancellable>, move |result| {
+                // An error here means the dialog was dismissed
+                if let Some(path) = result.ok().and_then(|file| file.path()) {
+                    button.set_label(&file_label(&Some(path.clone()), placeholder));
+                    *selection.borrow_mut() = Some(path);
+                    on_changed();
+                }
+            });
+        }
+    });
+    button
+}
+
+/// Generates a password from the saved settings for `--pass PATH` and inserts
+/// it without opening a window. Returns the process exit code.
+fn insert_into_pass(name: &str) -> i32 {
+    let password = match generate_from_settings(&load_settings()) {
+        Some(password) => password,
+        None => {
+            eprintln!("No character set is enabled in the settings");
+            return 2;
+        }
+    };
+    match PasswordStore::open_default().insert(name, &password, false) {
+        Ok(file) => {
+            println!("Inserted {} into {}", name, file.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("Failed to insert into pass: {}", err);
+            1
+        }
+    }
+}
+
+/// Generates `count` passwords from the saved settings for `--count N`, printed
+/// to stdout or written to `output`. Returns the process exit code.
+fn export_bulk(count: i32, format: Option<&str>, output: Option<&Path>, usernames: Option<&Path>) -> i32 {
+    let format = match format.map(ExportFormat::parse) {
+        None => ExportFormat::Csv,
+        Some(Some(format)) => format,
+        Some(None) => {
+            eprintln!("Unknown format; use csv, json, bitwarden or 1password");
+            return 2;
+        }
+    };
+    let usernames = match usernames.map(fs::read_to_string) {
+        None => Vec::new(),
+        Some(Ok(text)) => bulk::parse_usernames(&text),
+        Some(Err(err)) => {
+            eprintln!("Failed to read usernames: {}", err);
+            return 1;
+        }
+    };
+    if count < 1 && usernames.is_empty() {
+        eprintln!("--count must be at least 1");
+        return 2;
+    }
+
+    let settings = load_settings();
+    if generate_from_settings(&settings).is_none() {
+        eprintln!("No character set is enabled in the settings");
+        return 2;
+    }
+    let items = bulk::generate_batch(count.max(0) as usize, &usernames, || {
+        generate_from_settings(&settings).unwrap_or_default()
+    });
+    let contents = bulk::render(format, &items);
+
+    match output {
+        Some(path) => match bulk::write_private(path, &contents) {
+            Ok(()) => {
+                println!("Wrote {} passwords to {}", items.len(), path.display());
+                eprintln!("Warning: this file contains plain-text passwords; delete it once they are imported");
+                0
+            }
+            Err(err) => {
+                eprintln!("Failed to write {}: {}", path.display(), err);
+                1
+            }
+        },
+        None => {
+            print!("{}", contents.as_str());
+            0
+        }
+    }
+}
+
+/// Generates a password the way the window would with `settings`, or `None`
+/// when no character set is enabled.
+fn generate_from_settings(settings: &AppSettings) -> Option<SecretString> {
+    let options = GenerationOptions::new(
+        settings.allow_lowercase,
+        settings.allow_uppercase,
+        settings.allow_digits,
+        settings.allow_special,
+    );
+    if !settings.default_strategy && !options.is_valid() {
+        return None;
+    }
+    Some(generate_password(settings.groups, &options, settings.default_strategy))
+}
+
+/// Exact entropy of `generate_password` output for the given settings.
+fn password_entropy_bits(groups: i32, options: &GenerationOptions, use_default_strategy: bool) -> f64 {
+    let total_chars = (groups.max(1) * 5) as usize;
+
+    if use_default_strategy {
+        let forced_pools: Vec<&[u8]> = [
+            (options.uppercase, UPPER),
+            (options.digits, DIGITS),
+            (options.special, SPECIAL),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, pool)| enabled.then_some(pool))
+        .collect();
+        let forced = forced_pools.len().min(total_chars);
+        // Ordered choice of forced positions, a character from each forced pool, lowercase elsewhere
+        let placements: f64 = ((total_chars - forced + 1)..=total_chars)
+            .map(|n| (n as f64).log2())
+            .sum();
+        let forced_chars: f64 = forced_pools.iter().map(|pool| (pool.len() as f64).log2()).sum();
+        placements + forced_chars + (total_chars - forced) as f64 * (LOWER.len() as f64).log2()
+    } else {
+        total_chars as f64 * (options.pool().len().max(1) as f64).log2()
+    }
+}
+
+fn log_clipboard_copy(strings: &I18nStrings, password: &SecretString) {
+    glib::g_info!(LOG_DOMAIN, "Password copied to clipboard");
+    glib::g_debug!(LOG_DOMAIN, "{}", strings.clipboard_log(password.as_str().chars().count()));
 }
 
 fn copy_to_clipboard(window: &ApplicationWindow, text: &str) {
@@ -1057,6 +3867,48 @@ fn copy_to_clipboard(window: &ApplicationWindow, text: &str) {
     clipboard.set_text(text);
 }
 
+/// Empties the clipboard, unless another application has replaced its
+/// content since.
+fn clear_clipboard() -> bool {
+    match gtk::gdk::Display::default() {
+        Some(display) if display.clipboard().is_local() => display
+            .clipboard()
+            .set_content(None::<&gtk::gdk::ContentProvider>)
+            .is_ok(),
+        _ => false,
+    }
+}
+
+/// Sends a desktop notification that replaces an earlier one with the same
+/// `id`. `buttons` pairs labels with application action names.
+fn send_notification(app: &impl IsA<gio::Application>, title: &str, id: &str, body: &str, buttons: &[(&str, &str)]) {
+    let notification = gio::Notification::new(title);
+    notification.set_body(Some(body));
+    for (label, action) in buttons {
+        notification.add_button(label, action);
+    }
+    app.send_notification(Some(id), &notification);
+}
+
+/// Copies the selected text of the focused text field other than the password
+/// entry, so Ctrl+C keeps working in fields like the analyzer's. Returns
+/// false if there is no such selection.
+fn copy_focused_selection(window: &ApplicationWindow, password_entry: &Entry) -> bool {
+    let focus = match gtk::prelude::GtkWindowExt::focus(window) {
+        Some(widget) => widget,
+        None => return false,
+    };
+    if focus.is_ancestor(password_entry) || focus == *password_entry.upcast_ref::<gtk::Widget>() {
+        return false;
+    }
+    match focus.dynamic_cast_ref::<gtk::Editable>() {
+        Some(editable) if editable.selection_bounds().is_some() => {
+            focus.activate_action("clipboard.copy", None).is_ok()
+        }
+        _ => false,
+    }
+}
+
 fn window_is_active(window: &ApplicationWindow) -> bool {
     window.upcast_ref::<gtk::Window>().is_active()
 }
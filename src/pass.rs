@@ -0,0 +1,285 @@
+//! Inserting passwords into a `pass` password store.
+//!
+//! Entries are written the same way `pass insert` does: the password is piped
+//! into `gpg --encrypt` for the recipients listed in the nearest `.gpg-id`,
+//! and the new file is committed when the store is a git repository. gpg
+//! writes to a hidden file next to the entry, which only replaces the entry
+//! once encryption succeeded.
+
+use crate::logging::LOG_DOMAIN;
+use crate::secret::SecretString;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+
+pub const STORE_DIR_ENV_VAR: &str = "PASSWORD_STORE_DIR";
+const GPG_ID_FILE: &str = ".gpg-id";
+const ENTRY_EXTENSION: &str = "gpg";
+
+pub struct PasswordStore {
+    root: PathBuf,
+}
+
+impl PasswordStore {
+    /// The store `pass` itself would use: `$PASSWORD_STORE_DIR` or `~/.password-store`.
+    pub fn open_default() -> Self {
+        let root = match std::env::var_os(STORE_DIR_ENV_VAR) {
+            Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+            _ => glib::home_dir().join(".password-store"),
+        };
+        Self::new(root)
+    }
+
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Folders below the store root, relative to it, for the path picker.
+    pub fn folders(&self) -> Vec<String> {
+        let mut folders = Vec::new();
+        collect_folders(&self.root, Path::new(""), &mut folders);
+        folders.sort();
+        folders
+    }
+
+    /// Encrypts `password` into `<root>/<name>.gpg` and returns the entry file.
+    pub fn insert(&self, name: &str, password: &SecretString, overwrite: bool) -> io::Result<PathBuf> {
+        let relative = entry_path(name)?;
+        let file = self.root.join(&relative);
+        if file.exists() && !overwrite {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", name),
+            ));
+        }
+
+        let recipients = self.recipients(&file)?;
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let partial = partial_path(&file);
+        if let Err(err) = encrypt(&partial, &recipients, password).and_then(|()| std::fs::rename(&partial, &file)) {
+            let _ = std::fs::remove_file(&partial);
+            return Err(err);
+        }
+        glib::g_info!(LOG_DOMAIN, "Password inserted into password store");
+
+        if self.root.join(".git").exists() {
+            if let Err(err) = self.commit(&relative, name) {
+                glib::g_warning!(LOG_DOMAIN, "Failed to commit password store entry: {}", err);
+            }
+        }
+        Ok(file)
+    }
+
+    /// Recipients from the `.gpg-id` closest to `file`, like `pass` resolves them.
+    fn recipients(&self, file: &Path) -> io::Result<Vec<String>> {
+        let mut dir = file.parent();
+        while let Some(current) = dir {
+            if !current.starts_with(&self.root) {
+                break;
+            }
+            let id_file = current.join(GPG_ID_FILE);
+            if id_file.is_file() {
+                let recipients = parse_gpg_ids(&std::fs::read_to_string(&id_file)?);
+                if recipients.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{} lists no recipients", id_file.display()),
+                    ));
+                }
+                return Ok(recipients);
+            }
+            dir = current.parent();
+        }
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no {} found; run `pass init` first", GPG_ID_FILE),
+        ))
+    }
+
+    fn commit(&self, relative: &Path, name: &str) -> io::Result<()> {
+        run_git(&self.root, &["add", "--"], relative)?;
+        let message = format!("Add generated password for {}.", name);
+        run_git(&self.root, &["commit", "-m", &message, "--"], relative)
+    }
+}
+
+/// Relative file for an entry name such as `web/example.com`.
+fn entry_path(name: &str) -> io::Result<PathBuf> {
+    let trimmed = name.trim().trim_matches('/');
+    let invalid = || io::Error::new(io::ErrorKind::InvalidInput, format!("invalid entry name: {:?}", name));
+    if trimmed.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut path = PathBuf::new();
+    for component in Path::new(trimmed).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            _ => return Err(invalid()),
+        }
+    }
+    let file_name = format!("{}.{}", path.file_name().ok_or_else(invalid)?.to_string_lossy(), ENTRY_EXTENSION);
+    path.set_file_name(file_name);
+    Ok(path)
+}
+
+/// Where gpg writes `file` before it is renamed into place; hidden, so
+/// neither `pass` nor the folder picker lists it.
+fn partial_path(file: &Path) -> PathBuf {
+    let name = file.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    file.with_file_name(format!(".{}.partial", name))
+}
+
+fn parse_gpg_ids(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|id| !id.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+fn collect_folders(dir: &Path, relative: &Path, folders: &mut Vec<String>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false) {
+            let child = relative.join(&name);
+            folders.push(child.to_string_lossy().into_owned());
+            collect_folders(&entry.path(), &child, folders);
+        }
+    }
+}
+
+fn encrypt(file: &Path, recipients: &[String], password: &SecretString) -> io::Result<()> {
+    let mut command = Command::new("gpg");
+    command
+        .args(["--encrypt", "--quiet", "--yes", "--batch", "--compress-algo=none", "--no-encrypt-to"])
+        .arg("--output")
+        .arg(file);
+    for recipient in recipients {
+        command.arg("--recipient").arg(recipient);
+    }
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let written = stdin
+            .write_all(password.as_str().as_bytes())
+            .and_then(|()| stdin.write_all(b"\n"));
+        if let Err(err) = written {
+            // Stop gpg before it writes out a truncated password, and reap it
+            drop(stdin);
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(err);
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "gpg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+fn run_git(root: &Path, args: &[&str], path: &Path) -> io::Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .arg(path)
+        .stdin(Stdio::null())
+        .output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("passwordgenerator-pass-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn entry_names_map_to_gpg_files_inside_the_store() {
+        assert_eq!(entry_path("web/example.com").unwrap(), Path::new("web/example.com.gpg"));
+        assert_eq!(entry_path(" /mail/work/ ").unwrap(), Path::new("mail/work.gpg"));
+        for name in ["", "/", "../outside", "web/../../outside", "./web"] {
+            assert!(entry_path(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn nearest_gpg_id_wins() {
+        let root = temp_store("recipients");
+        std::fs::create_dir_all(root.join("work/servers")).unwrap();
+        std::fs::write(root.join(GPG_ID_FILE), "me@example.com\n").unwrap();
+        std::fs::write(root.join("work").join(GPG_ID_FILE), "# team keys\nalice@example.com\n\nbob@example.com # ops\n").unwrap();
+        let store = PasswordStore::new(root.clone());
+
+        assert_eq!(store.recipients(&root.join("web.gpg")).unwrap(), ["me@example.com"]);
+        assert_eq!(
+            store.recipients(&root.join("work/servers/db.gpg")).unwrap(),
+            ["alice@example.com", "bob@example.com"]
+        );
+        assert_eq!(store.folders(), ["work", "work/servers"]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn insert_refuses_uninitialized_stores_and_existing_entries() {
+        let root = temp_store("insert");
+        let store = PasswordStore::new(root.clone());
+        let password = SecretString::from("kx7Qd-T2mzp-9vLwe");
+        assert_eq!(store.insert("web", &password, false).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        std::fs::write(root.join("web.gpg"), b"").unwrap();
+        assert_eq!(store.insert("web", &password, false).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn failed_encryption_keeps_the_existing_entry() {
+        let root = temp_store("overwrite");
+        std::fs::write(root.join(GPG_ID_FILE), "nobody@invalid.example\n").unwrap();
+        std::fs::write(root.join("web.gpg"), b"old entry").unwrap();
+        let store = PasswordStore::new(root.clone());
+
+        // Fails whether gpg is missing or has no key for the recipient
+        assert!(store.insert("web", &SecretString::from("kx7Qd-T2mzp-9vLwe"), true).is_err());
+        assert_eq!(std::fs::read(root.join("web.gpg")).unwrap(), b"old entry");
+        assert!(!partial_path(&root.join("web.gpg")).exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,195 @@
+//! Reading `.po` files and compiling them to `.mo` catalogs. Shared by the
+//! build script and the tests that check the translations.
+
+#[derive(Debug)]
+pub struct PoEntry {
+    pub context: Option<String>,
+    pub msgid: String,
+    pub msgstr: String,
+    pub fuzzy: bool,
+}
+
+/// Parses the entries of a `.po` or `.pot` file, without obsolete entries.
+/// The header is the entry with an empty message id. Plural forms are not
+/// used yet.
+pub fn parse(text: &str) -> Vec<PoEntry> {
+    fn unquote(line: &str) -> String {
+        let line = line.trim();
+        let inner = line.strip_prefix('"').and_then(|line| line.strip_suffix('"')).unwrap_or(line);
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        }
+        out
+    }
+
+    enum Field {
+        Context,
+        Msgid,
+        Msgstr,
+        Other,
+    }
+
+    let mut entries = Vec::new();
+    for block in text.split("\n\n") {
+        let mut context: Option<String> = None;
+        let mut msgid: Option<String> = None;
+        let mut msgstr = String::new();
+        let mut fuzzy = false;
+        // The field that continuation lines belong to
+        let mut field = Field::Other;
+        for line in block.lines() {
+            let value = if let Some(rest) = line.strip_prefix("msgctxt ") {
+                field = Field::Context;
+                rest
+            } else if let Some(rest) = line.strip_prefix("msgid ") {
+                field = Field::Msgid;
+                rest
+            } else if let Some(rest) = line.strip_prefix("msgstr ") {
+                field = Field::Msgstr;
+                rest
+            } else if line.starts_with('"') {
+                line
+            } else {
+                if line.starts_with("#,") {
+                    fuzzy |= line.contains("fuzzy");
+                } else if !line.starts_with('#') {
+                    field = Field::Other;
+                }
+                continue;
+            };
+            let text = match field {
+                Field::Context => context.get_or_insert_with(String::new),
+                Field::Msgid => msgid.get_or_insert_with(String::new),
+                Field::Msgstr => &mut msgstr,
+                Field::Other => continue,
+            };
+            text.push_str(&unquote(value));
+        }
+        if let Some(msgid) = msgid {
+            entries.push(PoEntry {
+                context,
+                msgid,
+                msgstr,
+                fuzzy,
+            });
+        }
+    }
+    entries
+}
+
+/// Compiles `entries` to a GNU `.mo` catalog like `msgfmt` does, leaving out
+/// fuzzy and untranslated messages but always keeping the header.
+pub fn to_mo(entries: &[PoEntry]) -> Vec<u8> {
+    const MAGIC: u32 = 0x9504_12de;
+    const HEADER_LEN: usize = 7 * 4;
+
+    let mut messages: Vec<(String, &str)> = entries
+        .iter()
+        .filter(|entry| !entry.msgstr.is_empty() && (entry.msgid.is_empty() || !entry.fuzzy))
+        .map(|entry| {
+            // gettext looks up messages with a context as "context\x04msgid"
+            let key = match &entry.context {
+                Some(context) => format!("{}\u{4}{}", context, entry.msgid),
+                None => entry.msgid.clone(),
+            };
+            (key, entry.msgstr.as_str())
+        })
+        .collect();
+    // Without a hash table, gettext finds messages by binary search
+    messages.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let count = messages.len();
+    let originals_offset = HEADER_LEN;
+    let translations_offset = originals_offset + count * 8;
+    let strings_offset = translations_offset + count * 8;
+
+    let mut tables = Vec::with_capacity(count * 16);
+    let mut strings = Vec::new();
+    let originals = messages.iter().map(|(key, _)| key.as_str());
+    let translations = messages.iter().map(|(_, msgstr)| *msgstr);
+    for text in originals.chain(translations) {
+        tables.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        tables.extend_from_slice(&((strings_offset + strings.len()) as u32).to_le_bytes());
+        strings.extend_from_slice(text.as_bytes());
+        strings.push(0);
+    }
+
+    let mut mo = Vec::with_capacity(strings_offset + strings.len());
+    for field in [
+        MAGIC,
+        0,
+        count as u32,
+        originals_offset as u32,
+        translations_offset as u32,
+        0,
+        strings_offset as u32,
+    ] {
+        mo.extend_from_slice(&field.to_le_bytes());
+    }
+    mo.extend_from_slice(&tables);
+    mo.extend_from_slice(&strings);
+    mo
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Looks `key` up the way gettext does without a hash table.
+    fn lookup<'a>(mo: &'a [u8], key: &str) -> Option<&'a str> {
+        let word = |offset: usize| u32::from_le_bytes(mo[offset..offset + 4].try_into().unwrap()) as usize;
+        let string = |table: usize, index: usize| {
+            let (len, offset) = (word(table + index * 8), word(table + index * 8 + 4));
+            std::str::from_utf8(&mo[offset..offset + len]).unwrap()
+        };
+        assert_eq!(word(0), 0x9504_12de);
+        let (count, originals, translations) = (word(8), word(12), word(16));
+        let keys: Vec<&str> = (0..count).map(|index| string(originals, index)).collect();
+        let index = keys.binary_search(&key).ok()?;
+        Some(string(translations, index))
+    }
+
+    #[test]
+    fn parser_reads_the_header_context_continuations_and_flags() {
+        let entries = parse(concat!(
+            "msgid \"\"\nmsgstr \"\"\n\"Language: de\\n\"\n\n",
+            "#. Translators: a comment\n#, fuzzy\nmsgid \"Copied\"\nmsgstr \"Kopiert\"\n\n",
+            "msgctxt \"spelling alphabet\"\nmsgid \"alfa\"\nmsgstr \"\"\n\"an\"\n\"ton\"\n\n",
+            "msgid \"\"\n\"Say \\\"hi\\\"\"\nmsgstr \"Sag \\\"hallo\\\"\"\n",
+        ));
+        assert_eq!(entries.len(), 4);
+        assert_eq!((entries[0].msgid.as_str(), entries[0].msgstr.as_str()), ("", "Language: de\n"));
+        assert!(entries[1].fuzzy);
+        assert_eq!((entries[1].msgid.as_str(), entries[1].msgstr.as_str()), ("Copied", "Kopiert"));
+        assert_eq!(entries[2].context.as_deref(), Some("spelling alphabet"));
+        assert_eq!(entries[2].msgstr, "anton");
+        assert_eq!((entries[3].msgid.as_str(), entries[3].msgstr.as_str()), ("Say \"hi\"", "Sag \"hallo\""));
+    }
+
+    #[test]
+    fn catalog_holds_the_translated_messages() {
+        let mo = to_mo(&parse(concat!(
+            "#, fuzzy\nmsgid \"\"\nmsgstr \"Language: de\\n\"\n\n",
+            "msgid \"Generate\"\nmsgstr \"Erzeugen\"\n\n",
+            "#, fuzzy\nmsgid \"Copied\"\nmsgstr \"Kopiert\"\n\n",
+            "msgid \"Save\"\nmsgstr \"\"\n\n",
+            "msgctxt \"spelling alphabet\"\nmsgid \"alfa\"\nmsgstr \"anton\"\n",
+        )));
+        assert_eq!(lookup(&mo, ""), Some("Language: de\n"));
+        assert_eq!(lookup(&mo, "Generate"), Some("Erzeugen"));
+        assert_eq!(lookup(&mo, "spelling alphabet\u{4}alfa"), Some("anton"));
+        assert_eq!(lookup(&mo, "Copied"), None);
+        assert_eq!(lookup(&mo, "Save"), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Shared plumbing for XDG desktop portals, which answer most calls through a
+//! Request object's `Response` signal instead of the method reply.
+
+use futures_channel::oneshot;
+use glib::variant::ObjectPath;
+use glib::{ToVariant, Variant};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+pub const PORTAL_NAME: &str = "org.freedesktop.portal.Desktop";
+pub const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+pub const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+const SESSION_INTERFACE: &str = "org.freedesktop.portal.Session";
+
+pub struct Portal {
+    connection: gio::DBusConnection,
+    bus_name: String,
+}
+
+pub fn unexpected_reply(method: &str) -> glib::Error {
+    glib::Error::new(
+        gio::IOErrorEnum::InvalidData,
+        &format!("Unexpected reply to {} from the desktop portal", method),
+    )
+}
+
+/// Where the portal exports the Request object for `token` of a caller.
+pub fn request_path(unique_name: &str, token: &str) -> String {
+    format!(
+        "{}/request/{}/{}",
+        PORTAL_PATH,
+        unique_name.trim_start_matches(':').replace('.', "_"),
+        token
+    )
+}
+
+/// A fresh token for the `handle_token` and `session_handle_token` options.
+fn new_token() -> String {
+    format!("passwordgenerator{}", rand::random::<u32>())
+}
+
+impl Portal {
+    pub async fn session() -> Result<Self, glib::Error> {
+        let connection = gio::bus_get_future(gio::BusType::Session).await?;
+        Ok(Self::with_connection(connection, PORTAL_NAME))
+    }
+
+    pub fn with_connection(connection: gio::DBusConnection, bus_name: &str) -> Self {
+        Self {
+            connection,
+            bus_name: bus_name.to_string(),
+        }
+    }
+
+    pub fn connection(&self) -> &gio::DBusConnection {
+        &self.connection
+    }
+
+    pub fn bus_name(&self) -> &str {
+        &self.bus_name
+    }
+
+    /// A plain method call on the portal object.
+    pub async fn call(&self, interface: &str, method: &str, parameters: Variant) -> Result<Variant, glib::Error> {
+        self.connection
+            .call_future(
+                Some(&self.bus_name),
+                PORTAL_PATH,
+                interface,
+                method,
+                Some(&parameters),
+                None,
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+    }
+
+    /// Calls a method that answers through a Request object and waits for its
+    /// Response. `options` is sent as the trailing `a{sv}` argument.
+    pub async fn request(
+        &self,
+        interface: &str,
+        method: &str,
+        mut args: Vec<Variant>,
+        mut options: HashMap<&'static str, Variant>,
+    ) -> Result<HashMap<String, Variant>, glib::Error> {
+        let token = new_token();
+        let unique_name = self
+            .connection
+            .unique_name()
+            .ok_or_else(|| glib::Error::new(gio::IOErrorEnum::NotConnected, "Not connected to the session bus"))?;
+        // The request path is predictable, so subscribe before calling to never miss the response
+        let request_path = request_path(&unique_name, &token);
+
+        let (sender, receiver) = oneshot::channel::<(u32, HashMap<String, Variant>)>();
+        let sender = RefCell::new(Some(sender));
+        let subscription = self.connection.signal_subscribe(
+            Some(&self.bus_name),
+            Some(REQUEST_INTERFACE),
+            Some("Response"),
+            Some(&request_path),
+            None,
+            gio::DBusSignalFlags::NONE,
+            move |_, _, _, _, _, parameters| {
+                if let Some(response) = parameters.get::<(u32, HashMap<String, Variant>)>() {
+                    if let Some(sender) = sender.borrow_mut().take() {
+                        let _ = sender.send(response);
+                    }
+                }
+            },
+        );
+
+        options.insert("handle_token", token.to_variant());
+        args.push(options.to_variant());
+        let called = self.call(interface, method, Variant::tuple_from_iter(args)).await;
+        let response = match called {
+            Ok(_) => receiver.await.ok(),
+            Err(err) => {
+                self.connection.signal_unsubscribe(subscription);
+                return Err(err);
+            }
+        };
+        self.connection.signal_unsubscribe(subscription);
+
+        match response {
+            Some((0, results)) => Ok(results),
+            Some((1, _)) => Err(glib::Error::new(
+                gio::IOErrorEnum::Cancelled,
+                &format!("{} was cancelled", method),
+            )),
+            _ => Err(glib::Error::new(
+                gio::IOErrorEnum::Failed,
+                &format!("{} failed", method),
+            )),
+        }
+    }
+
+    /// Creates a session on `interface` and returns its handle.
+    pub async fn create_session(&self, interface: &str) -> Result<ObjectPath, glib::Error> {
+        let results = self
+            .request(
+                interface,
+                "CreateSession",
+                Vec::new(),
+                HashMap::from([("session_handle_token", new_token().to_variant())]),
+            )
+            .await?;
+        // Documented as a string, although it holds an object path
+        results
+            .get("session_handle")
+            .and_then(|handle| handle.get::<String>())
+            .and_then(|handle| ObjectPath::try_from(handle).ok())
+            .ok_or_else(|| unexpected_reply("CreateSession"))
+    }
+
+    pub async fn close_session(&self, session: &ObjectPath) -> Result<(), glib::Error> {
+        self.connection
+            .call_future(
+                Some(&self.bus_name),
+                session.as_str(),
+                SESSION_INTERFACE,
+                "Close",
+                None,
+                None,
+                gio::DBusCallFlags::NONE,
+                -1,
+            )
+            .await
+            .map(|_| ())
+    }
+}
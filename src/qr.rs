@@ -0,0 +1,88 @@
+//! QR codes for getting a password onto a phone without retyping it. They are
+//! rendered locally; nothing leaves the machine.
+
+use gtk4::cairo;
+use qrcode::{Color, QrCode};
+use zeroize::Zeroizing;
+
+/// Light modules around the code, as required by the QR specification.
+const QUIET_ZONE: usize = 4;
+
+/// The `WIFI:` payload phone cameras offer to join a WPA network with.
+pub fn wifi_payload(ssid: &str, password: &str) -> Zeroizing<String> {
+    let mut payload = Zeroizing::new(String::from("WIFI:T:WPA;S:"));
+    push_escaped(&mut payload, ssid);
+    payload.push_str(";P:");
+    push_escaped(&mut payload, password);
+    payload.push_str(";;");
+    payload
+}
+
+fn push_escaped(out: &mut String, value: &str) {
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
+
+/// Dark modules of the QR code for `text`, row by row, plus the code's width.
+/// `None` when the text is empty or too long to encode.
+pub fn modules(text: &str) -> Option<(usize, Vec<bool>)> {
+    if text.is_empty() {
+        return None;
+    }
+    let code = QrCode::new(text.as_bytes()).ok()?;
+    let dark = code.to_colors().into_iter().map(|color| color == Color::Dark).collect();
+    Some((code.width(), dark))
+}
+
+/// Draws the code centered in a `width` x `height` area, dark on white
+/// regardless of the theme so cameras can read it.
+pub fn draw(cr: &cairo::Context, modules: &(usize, Vec<bool>), width: i32, height: i32) {
+    let (size, dark) = modules;
+    let total = (size + 2 * QUIET_ZONE) as f64;
+    // Whole pixels per module keep the edges sharp
+    let scale = (f64::from(width.min(height)) / total).floor().max(1.0);
+    let left = ((f64::from(width) - total * scale) / 2.0).floor();
+    let top = ((f64::from(height) - total * scale) / 2.0).floor();
+
+    cr.set_source_rgb(1.0, 1.0, 1.0);
+    cr.rectangle(left, top, total * scale, total * scale);
+    let _ = cr.fill();
+
+    cr.set_source_rgb(0.0, 0.0, 0.0);
+    for (index, _) in dark.iter().enumerate().filter(|(_, dark)| **dark) {
+        let x = (index % size + QUIET_ZONE) as f64;
+        let y = (index / size + QUIET_ZONE) as f64;
+        cr.rectangle(left + x * scale, top + y * scale, scale, scale);
+    }
+    let _ = cr.fill();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wifi_payload_escapes_special_characters() {
+        assert_eq!(
+            wifi_payload("Home", "abcde-FGHIJ").as_str(),
+            "WIFI:T:WPA;S:Home;P:abcde-FGHIJ;;"
+        );
+        assert_eq!(
+            wifi_payload("Café;\"5G\"", r"a:b,c\d").as_str(),
+            r#"WIFI:T:WPA;S:Café\;\"5G\";P:a\:b\,c\\d;;"#
+        );
+    }
+
+    #[test]
+    fn modules_form_a_square_code() {
+        let (width, dark) = modules("abcde-FGHIJ-12345").unwrap();
+        assert_eq!(dark.len(), width * width);
+        // Finder pattern in the top-left corner
+        assert!(dark[0] && dark[6] && !dark[width + 1]);
+        assert!(modules("").is_none());
+    }
+}
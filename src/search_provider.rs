@@ -0,0 +1,340 @@
+//! GNOME Shell search provider (`org.gnome.Shell.SearchProvider2`).
+//!
+//! Queries such as "pw 20", "password nospecial", "pw pin 6" or "pw words 5"
+//! show a freshly generated password in the overview. The result carries the
+//! password as `clipboardText`, so the shell copies it when it is activated:
+//! a D-Bus activated instance has no window and exits soon after, so it cannot
+//! own the clipboard itself.
+
+use crate::logging::LOG_DOMAIN;
+use crate::secret::SecretString;
+use crate::{
+    generate_passphrase, generate_password, generate_pin, AppSettings, GenerationOptions, APP_ID, MAX_GROUPS,
+};
+use glib::{ToVariant, Variant};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+pub const INTERFACE_NAME: &str = "org.gnome.Shell.SearchProvider2";
+pub const OBJECT_PATH_SUFFIX: &str = "/SearchProvider";
+
+const INTROSPECTION_XML: &str = r#"
+<node>
+  <interface name="org.gnome.Shell.SearchProvider2">
+    <method name="GetInitialResultSet">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetSubsearchResultSet">
+      <arg type="as" name="previous_results" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="as" name="results" direction="out"/>
+    </method>
+    <method name="GetResultMetas">
+      <arg type="as" name="identifiers" direction="in"/>
+      <arg type="aa{sv}" name="metas" direction="out"/>
+    </method>
+    <method name="ActivateResult">
+      <arg type="s" name="identifier" direction="in"/>
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+    <method name="LaunchSearch">
+      <arg type="as" name="terms" direction="in"/>
+      <arg type="u" name="timestamp" direction="in"/>
+    </method>
+  </interface>
+</node>"#;
+
+const TRIGGERS: [&str; 2] = ["pw", "password"];
+const DEFAULT_PIN_DIGITS: usize = 6;
+const PIN_DIGITS: std::ops::RangeInclusive<usize> = 4..=20;
+const DEFAULT_WORDS: usize = 5;
+const WORD_COUNTS: std::ops::RangeInclusive<usize> = 3..=12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Query {
+    Password {
+        groups: i32,
+        options: GenerationOptions,
+        use_default_strategy: bool,
+    },
+    Pin(usize),
+    Words(usize),
+}
+
+impl Query {
+    /// Parses overview search terms; `None` when the search is not meant for us.
+    fn parse(terms: &[String], settings: &AppSettings) -> Option<Self> {
+        let mut triggered = false;
+        let mut pin = false;
+        let mut words = false;
+        let mut no_special = false;
+        let mut number = None;
+
+        for term in terms {
+            let term = term.to_lowercase();
+            match term.as_str() {
+                term if TRIGGERS.contains(&term) => triggered = true,
+                "pin" => pin = true,
+                "words" => words = true,
+                "nospecial" => no_special = true,
+                _ => number = Some(term.parse::<usize>().ok()?),
+            }
+        }
+
+        match (pin, words) {
+            _ if !triggered => None,
+            (true, true) => None,
+            (true, false) => {
+                let digits = number.unwrap_or(DEFAULT_PIN_DIGITS);
+                PIN_DIGITS.contains(&digits).then_some(Query::Pin(digits))
+            }
+            (false, true) => {
+                let count = number.unwrap_or(DEFAULT_WORDS);
+                WORD_COUNTS.contains(&count).then_some(Query::Words(count))
+            }
+            (false, false) => {
+                // Lengths are in characters, rounded up to whole groups of five
+                let groups = match number {
+                    Some(length) => i32::try_from(length.div_ceil(5))
+                        .ok()
+                        .filter(|groups| (1..=MAX_GROUPS).contains(groups))?,
+                    None => settings.groups,
+                };
+                let options = GenerationOptions::new(
+                    settings.allow_lowercase,
+                    settings.allow_uppercase,
+                    settings.allow_digits,
+                    settings.allow_special && !no_special,
+                );
+                let use_default_strategy = settings.default_strategy;
+                if !use_default_strategy && !options.is_valid() {
+                    return None;
+                }
+                Some(Query::Password {
+                    groups,
+                    options,
+                    use_default_strategy,
+                })
+            }
+        }
+    }
+
+    fn generate(self) -> SecretString {
+        match self {
+            Query::Password {
+                groups,
+                options,
+                use_default_strategy,
+            } => generate_password(groups, &options, use_default_strategy),
+            Query::Pin(digits) => generate_pin(digits),
+            Query::Words(count) => generate_passphrase(count),
+        }
+    }
+}
+
+/// Passwords shown in the overview, keyed by result identifier. Only the
+/// latest search is kept so older passwords are wiped as soon as possible.
+#[derive(Default)]
+struct Results {
+    next_id: Cell<u64>,
+    current: RefCell<HashMap<String, SecretString>>,
+}
+
+impl Results {
+    fn search(&self, terms: &[String], settings: &AppSettings) -> Vec<String> {
+        let mut current = self.current.borrow_mut();
+        current.clear();
+        match Query::parse(terms, settings) {
+            Some(query) => {
+                let id = format!("password-{}", self.next_id.get());
+                self.next_id.set(self.next_id.get() + 1);
+                current.insert(id.clone(), query.generate());
+                vec![id]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn metas(&self, identifiers: &[String], description: &str) -> Vec<HashMap<String, Variant>> {
+        let current = self.current.borrow();
+        identifiers
+            .iter()
+            .filter_map(|id| {
+                let password = current.get(id)?;
+                let mut meta = HashMap::new();
+                meta.insert("id".to_owned(), id.to_variant());
+                meta.insert("name".to_owned(), password.as_str().to_variant());
+                meta.insert("description".to_owned(), description.to_variant());
+                meta.insert("gicon".to_owned(), APP_ID.to_variant());
+                // Lets the shell copy the result itself, like the calculator does
+                meta.insert("clipboardText".to_owned(), password.as_str().to_variant());
+                Some(meta)
+            })
+            .collect()
+    }
+
+    fn take(&self, identifier: &str) -> Option<SecretString> {
+        self.current.borrow_mut().remove(identifier)
+    }
+}
+
+pub struct Callbacks {
+    pub load_settings: fn() -> AppSettings,
+    /// Runs before every call, e.g. to keep a D-Bus activated app alive.
+    pub on_call: Box<dyn Fn()>,
+    pub launch: Box<dyn Fn()>,
+}
+
+/// Exports the search provider at `object_path`.
+pub fn register(
+    connection: &gio::DBusConnection,
+    object_path: &str,
+    description: &'static str,
+    callbacks: Callbacks,
+) -> Result<gio::RegistrationId, glib::Error> {
+    let node = gio::DBusNodeInfo::for_xml(INTROSPECTION_XML)?;
+    let info = node
+        .lookup_interface(INTERFACE_NAME)
+        .expect("introspection data declares the interface");
+    let results = Results::default();
+    connection
+        .register_object(object_path, &info)
+        .method_call(move |_, _, _, _, method, parameters, invocation| {
+            (callbacks.on_call)();
+            let reply = match method {
+                "GetInitialResultSet" => parameters
+                    .get::<(Vec<String>,)>()
+                    .map(|(terms,)| (results.search(&terms, &(callbacks.load_settings)()),).to_variant()),
+                "GetSubsearchResultSet" => parameters
+                    .get::<(Vec<String>, Vec<String>)>()
+                    .map(|(_, terms)| (results.search(&terms, &(callbacks.load_settings)()),).to_variant()),
+                "GetResultMetas" => parameters
+                    .get::<(Vec<String>,)>()
+                    .map(|(identifiers,)| (results.metas(&identifiers, description),).to_variant()),
+                "ActivateResult" => parameters.get::<(String, Vec<String>, u32)>().map(|(identifier, _, _)| {
+                    // The shell copies the result's clipboardText; only wipe our copy
+                    if results.take(&identifier).is_some() {
+                        glib::g_info!(LOG_DOMAIN, "Password copied from search result");
+                    }
+                    ().to_variant()
+                }),
+                "LaunchSearch" => {
+                    (callbacks.launch)();
+                    Some(().to_variant())
+                }
+                _ => None,
+            };
+            match reply {
+                Some(reply) => invocation.return_value(Some(&reply)),
+                None => invocation.return_dbus_error(
+                    "org.freedesktop.DBus.Error.InvalidArgs",
+                    &format!("invalid call to {}", method),
+                ),
+            }
+        })
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_bus;
+
+    fn terms(query: &str) -> Vec<String> {
+        query.split_whitespace().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn query_terms_map_to_generation_options() {
+        let settings = AppSettings::default();
+        let all = GenerationOptions::new(true, true, true, true);
+        let password = |groups, options| Query::Password {
+            groups,
+            options,
+            use_default_strategy: false,
+        };
+
+        assert_eq!(Query::parse(&terms("password"), &settings), Some(password(settings.groups, all)));
+        assert_eq!(Query::parse(&terms("pw 20"), &settings), Some(password(4, all)));
+        assert_eq!(Query::parse(&terms("PW 21"), &settings), Some(password(5, all)));
+        assert_eq!(
+            Query::parse(&terms("pw nospecial"), &settings),
+            Some(password(settings.groups, GenerationOptions::new(true, true, true, false)))
+        );
+        assert_eq!(Query::parse(&terms("pw pin"), &settings), Some(Query::Pin(6)));
+        assert_eq!(Query::parse(&terms("pw pin 8"), &settings), Some(Query::Pin(8)));
+        assert_eq!(Query::parse(&terms("password words 4"), &settings), Some(Query::Words(4)));
+
+        for ignored in [
+            "",
+            "passwords",
+            "password manager",
+            "pw 0",
+            "pw 51",
+            "pin",
+            "pin 6",
+            "words 5",
+            "pw pin 2",
+            "pw words 40",
+            "pw pin words",
+        ] {
+            assert_eq!(Query::parse(&terms(ignored), &settings), None, "{:?}", ignored);
+        }
+    }
+
+    #[test]
+    fn shell_can_search_fetch_and_activate_results() {
+        test_bus::run(|bus, context| {
+            let callbacks = Callbacks {
+                load_settings: AppSettings::default,
+                on_call: Box::new(|| {}),
+                launch: Box::new(|| {}),
+            };
+            let path = "/io/github/danst0/passwordgenerator/SearchProvider";
+            register(&bus.server, path, "Copy to clipboard", callbacks).unwrap();
+
+            let call = |method: &str, parameters: Variant| {
+                context
+                    .block_on(bus.client.call_future(
+                        Some(&bus.server_name()),
+                        path,
+                        INTERFACE_NAME,
+                        method,
+                        Some(&parameters),
+                        None,
+                        gio::DBusCallFlags::NONE,
+                        -1,
+                    ))
+                    .unwrap()
+            };
+
+            let (ids,) = call("GetInitialResultSet", (terms("pw pin 8"),).to_variant())
+                .get::<(Vec<String>,)>()
+                .unwrap();
+            assert_eq!(ids.len(), 1);
+            let (none,) = call("GetSubsearchResultSet", (ids.clone(), terms("pw pinboard")).to_variant())
+                .get::<(Vec<String>,)>()
+                .unwrap();
+            assert!(none.is_empty());
+
+            let (ids,) = call("GetInitialResultSet", (terms("pw pin 8"),).to_variant())
+                .get::<(Vec<String>,)>()
+                .unwrap();
+            let (metas,) = call("GetResultMetas", (ids.clone(),).to_variant())
+                .get::<(Vec<HashMap<String, Variant>>,)>()
+                .unwrap();
+            let pin: String = metas[0]["name"].get().unwrap();
+            assert_eq!(pin.len(), 8);
+            assert_eq!(metas[0]["clipboardText"].get::<String>(), Some(pin.clone()));
+
+            // Activating hands the copy to the shell and wipes the password here
+            call("ActivateResult", (ids[0].as_str(), terms("pw pin 8"), 0u32).to_variant());
+            let (metas,) = call("GetResultMetas", (ids,).to_variant())
+                .get::<(Vec<HashMap<String, Variant>>,)>()
+                .unwrap();
+            assert!(metas.is_empty());
+        });
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+use zeroize::Zeroize;
+
+/// A password held in memory that is wiped when dropped.
+///
+/// Deliberately not `Clone` and without a `Display` impl, so copies of the
+/// secret only appear where a caller explicitly asks for `as_str()`.
+#[derive(Default)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Self(value.to_owned())
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_output_is_redacted() {
+        let secret = SecretString::from("hunter2");
+        assert_eq!(format!("{:?}", secret), "SecretString(***)");
+        assert_eq!(secret.as_str(), "hunter2");
+    }
+}
@@ -0,0 +1,239 @@
+//! The `io.github.danst0.passwordgenerator.Generator` D-Bus interface, so
+//! launchers and scripts can request passwords without opening a window.
+
+use crate::logging::LOG_DOMAIN;
+use crate::{generate_password, AppSettings, GenerationOptions, MAX_GROUPS};
+use glib::{ToVariant, Variant};
+use std::collections::HashMap;
+
+pub const INTERFACE_NAME: &str = "io.github.danst0.passwordgenerator.Generator";
+const INVALID_ARGS_ERROR: &str = "org.freedesktop.DBus.Error.InvalidArgs";
+
+const INTROSPECTION_XML: &str = r#"
+<node>
+  <interface name="io.github.danst0.passwordgenerator.Generator">
+    <method name="Generate">
+      <arg type="a{sv}" name="options" direction="in"/>
+      <arg type="s" name="password" direction="out"/>
+    </method>
+    <method name="ListProfiles">
+      <arg type="as" name="profiles" direction="out"/>
+    </method>
+  </interface>
+</node>"#;
+
+/// Named starting points for `Generate`; "saved" follows the window's settings.
+pub const PROFILES: [&str; 4] = ["saved", "alphanumeric", "digits", "strong"];
+
+#[derive(Clone, Copy)]
+struct Request {
+    groups: i32,
+    options: GenerationOptions,
+    use_default_strategy: bool,
+}
+
+impl Request {
+    fn profile(name: &str, settings: &AppSettings) -> Option<Self> {
+        let saved = Self {
+            groups: settings.groups,
+            options: GenerationOptions::new(
+                settings.allow_lowercase,
+                settings.allow_uppercase,
+                settings.allow_digits,
+                settings.allow_special,
+            ),
+            use_default_strategy: settings.default_strategy,
+        };
+        let custom = |lowercase, uppercase, digits, special| Self {
+            options: GenerationOptions::new(lowercase, uppercase, digits, special),
+            use_default_strategy: false,
+            ..saved
+        };
+        match name {
+            "saved" => Some(saved),
+            "alphanumeric" => Some(custom(true, true, true, false)),
+            "digits" => Some(custom(false, false, true, false)),
+            "strong" => Some(Self {
+                groups: 6,
+                options: GenerationOptions::new(true, true, true, true),
+                use_default_strategy: true,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Applies the `Generate` options on top of the requested profile.
+    fn from_options(options: &HashMap<String, Variant>, settings: &AppSettings) -> Result<Self, String> {
+        let profile = match options.get("profile") {
+            Some(value) => value.get::<String>().ok_or("profile must be a string")?,
+            None => PROFILES[0].to_owned(),
+        };
+        let mut request = Self::profile(&profile, settings).ok_or_else(|| format!("unknown profile: {}", profile))?;
+
+        for (key, value) in options {
+            let flag = || value.get::<bool>().ok_or_else(|| format!("{} must be a boolean", key));
+            match key.as_str() {
+                "profile" => {}
+                "groups" => {
+                    request.groups = value
+                        .get::<i32>()
+                        .filter(|groups| (1..=MAX_GROUPS).contains(groups))
+                        .ok_or_else(|| format!("groups must be an integer from 1 to {}", MAX_GROUPS))?;
+                }
+                "lowercase" => request.options.lowercase = flag()?,
+                "uppercase" => request.options.uppercase = flag()?,
+                "digits" => request.options.digits = flag()?,
+                "special" => request.options.special = flag()?,
+                "default-strategy" => request.use_default_strategy = flag()?,
+                _ => return Err(format!("unknown option: {}", key)),
+            }
+        }
+
+        if !request.use_default_strategy && !request.options.is_valid() {
+            return Err("at least one character set must be enabled".to_owned());
+        }
+        Ok(request)
+    }
+}
+
+fn handle_call(method: &str, parameters: &Variant, settings: &AppSettings) -> Result<Variant, String> {
+    match method {
+        "Generate" => {
+            let (options,): (HashMap<String, Variant>,) = parameters.get().ok_or("expected a{sv}")?;
+            let request = Request::from_options(&options, settings)?;
+            let password = generate_password(request.groups, &request.options, request.use_default_strategy);
+            glib::g_info!(LOG_DOMAIN, "Password generated over D-Bus");
+            Ok((password.as_str(),).to_variant())
+        }
+        "ListProfiles" => Ok((PROFILES.to_vec(),).to_variant()),
+        _ => Err(format!("unknown method: {}", method)),
+    }
+}
+
+/// Exports the interface at `object_path`. `on_call` runs before every call,
+/// e.g. to keep a D-Bus activated application alive while it answers.
+pub fn register(
+    connection: &gio::DBusConnection,
+    object_path: &str,
+    load_settings: fn() -> AppSettings,
+    on_call: impl Fn() + 'static,
+) -> Result<gio::RegistrationId, glib::Error> {
+    let node = gio::DBusNodeInfo::for_xml(INTROSPECTION_XML)?;
+    let info = node
+        .lookup_interface(INTERFACE_NAME)
+        .expect("introspection data declares the interface");
+    connection
+        .register_object(object_path, &info)
+        .method_call(move |_, _, _, _, method, parameters, invocation| {
+            on_call();
+            match handle_call(method, &parameters, &load_settings()) {
+                Ok(reply) => invocation.return_value(Some(&reply)),
+                Err(message) => invocation.return_dbus_error(INVALID_ARGS_ERROR, &message),
+            }
+        })
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_bus;
+
+    const OBJECT_PATH: &str = "/io/github/danst0/passwordgenerator";
+
+    fn settings() -> AppSettings {
+        AppSettings {
+            groups: 2,
+            allow_special: false,
+            ..AppSettings::default()
+        }
+    }
+
+    fn call(
+        bus: &test_bus::PrivateBus,
+        context: &glib::MainContext,
+        method: &str,
+        parameters: Variant,
+    ) -> Result<Variant, glib::Error> {
+        context.block_on(bus.client.call_future(
+            Some(&bus.server_name()),
+            OBJECT_PATH,
+            INTERFACE_NAME,
+            method,
+            Some(&parameters),
+            None,
+            gio::DBusCallFlags::NONE,
+            -1,
+        ))
+    }
+
+    fn generate(
+        bus: &test_bus::PrivateBus,
+        context: &glib::MainContext,
+        options: &[(&str, Variant)],
+    ) -> Result<String, glib::Error> {
+        let options: HashMap<String, Variant> = options
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect();
+        let reply = call(bus, context, "Generate", (options,).to_variant())?;
+        Ok(reply.get::<(String,)>().unwrap().0)
+    }
+
+    #[test]
+    fn generates_passwords_from_profiles_and_overrides() {
+        test_bus::run(|bus, context| {
+            register(&bus.server, OBJECT_PATH, settings, || {}).unwrap();
+
+            let saved = generate(bus, context, &[]).unwrap();
+            assert_eq!(saved.len(), 11);
+            assert!(saved.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+
+            let digits = generate(
+                bus,
+                context,
+                &[("profile", "digits".to_variant()), ("groups", 3.to_variant())],
+            )
+            .unwrap();
+            assert_eq!(digits.len(), 17);
+            assert!(digits.split('-').all(|group| group.chars().all(|c| c.is_ascii_digit())));
+
+            let lowercase = generate(
+                bus,
+                context,
+                &[
+                    ("uppercase", false.to_variant()),
+                    ("digits", false.to_variant()),
+                ],
+            )
+            .unwrap();
+            assert!(lowercase.split('-').all(|group| group.chars().all(|c| c.is_ascii_lowercase())));
+
+            let reply = call(bus, context, "ListProfiles", ().to_variant()).unwrap();
+            assert_eq!(reply.get::<(Vec<String>,)>().unwrap().0, PROFILES);
+        });
+    }
+
+    #[test]
+    fn invalid_options_are_rejected() {
+        test_bus::run(|bus, context| {
+            register(&bus.server, OBJECT_PATH, settings, || {}).unwrap();
+
+            let invalid: [&[(&str, Variant)]; 5] = [
+                &[("profile", "unknown".to_variant())],
+                &[("groups", 0.to_variant())],
+                &[("groups", "three".to_variant())],
+                &[("colour", true.to_variant())],
+                &[
+                    ("lowercase", false.to_variant()),
+                    ("uppercase", false.to_variant()),
+                    ("digits", false.to_variant()),
+                ],
+            ];
+            for options in invalid {
+                let err = generate(bus, context, options).unwrap_err();
+                assert!(err.matches(gio::DBusError::InvalidArgs), "{:?}: {}", options, err);
+            }
+        });
+    }
+}
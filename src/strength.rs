@@ -0,0 +1,520 @@
+//! zxcvbn-style strength estimate for passwords pasted in by the user.
+//!
+//! The password is scanned for guessable patterns (common words, keyboard
+//! walks, sequences, dates, repeats), and the cheapest way to cover it with
+//! those patterns plus brute-forced gaps becomes the guess estimate.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroizing;
+
+/// An offline attack against a fast hash.
+const GUESSES_PER_SECOND: f64 = 1e10;
+const MIN_RECOMMENDED_LENGTH: usize = 12;
+const MIN_MATCH_LENGTH: usize = 3;
+const MAX_WORD_LENGTH: usize = 16;
+
+/// Most common first; a word's guess count is its rank.
+const COMMON_WORDS: &[&str] = &[
+    "password", "123456", "123456789", "qwerty", "12345678", "111111", "1234567890",
+    "1234567", "iloveyou", "admin", "welcome", "monkey", "login", "abc123", "starwars",
+    "123123", "dragon", "master", "hello", "freedom", "whatever", "qazwsx", "trustno1",
+    "654321", "jordan", "harley", "letmein", "sunshine", "princess", "football",
+    "baseball", "shadow", "superman", "michael", "charlie", "ashley", "bailey", "access",
+    "flower", "secret", "summer", "winter", "spring", "autumn", "love", "lovely",
+    "computer", "internet", "server", "google", "apple", "banana", "orange", "cheese",
+    "coffee", "pepper", "ginger", "hunter", "killer", "soccer", "hockey", "batman",
+    "mustang", "thomas", "robert", "daniel", "jessica", "jennifer", "michelle", "matthew",
+    "andrew", "joshua", "pass", "test", "guest", "user", "root", "default", "changeme",
+    "maggie", "buster", "tigger", "ranger", "cookie", "chocolate", "purple", "yellow",
+    "silver", "golden", "diamond", "angel", "family", "friend", "money", "magic",
+    "secure", "security", "passwort", "hallo", "geheim", "qwertz", "azerty", "correct",
+    "horse", "battery", "staple", "house", "tiger", "bear", "lion", "eagle", "blue",
+    "green", "black", "white", "happy", "moon", "star", "the", "and", "you", "dog", "cat",
+];
+
+const KEYBOARD_ROWS: &[&str] = &["`1234567890-=", "qwertyuiop[]\\", "asdfghjkl;'", "zxcvbnm,./"];
+const KEYBOARD_KEYS: f64 = 47.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feedback {
+    CommonWord,
+    L33tSubstitution,
+    KeyboardWalk,
+    Sequence,
+    Date,
+    Repeat,
+    TooShort,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Pattern {
+    Dictionary { l33t: bool },
+    KeyboardWalk,
+    Sequence,
+    Date,
+    Repeat,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Match {
+    start: usize,
+    end: usize,
+    guesses_log2: f64,
+    pattern: Pattern,
+}
+
+pub struct Analysis {
+    pub guesses_log2: f64,
+    pub feedback: Vec<Feedback>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrackTime {
+    Instant,
+    Seconds(u64),
+    Minutes(u64),
+    Hours(u64),
+    Days(u64),
+    Years(u64),
+    Centuries,
+}
+
+/// Time to exhaust `2^bits` guesses at [`GUESSES_PER_SECOND`].
+pub fn crack_time(bits: f64) -> CrackTime {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = 60.0 * MINUTE;
+    const DAY: f64 = 24.0 * HOUR;
+    const YEAR: f64 = 365.25 * DAY;
+
+    let seconds = bits.exp2() / GUESSES_PER_SECOND;
+    if seconds < 1.0 {
+        CrackTime::Instant
+    } else if seconds < MINUTE {
+        CrackTime::Seconds(seconds.round() as u64)
+    } else if seconds < HOUR {
+        CrackTime::Minutes((seconds / MINUTE).round() as u64)
+    } else if seconds < DAY {
+        CrackTime::Hours((seconds / HOUR).round() as u64)
+    } else if seconds < YEAR {
+        CrackTime::Days((seconds / DAY).round() as u64)
+    } else if seconds < 100.0 * YEAR {
+        CrackTime::Years((seconds / YEAR).round() as u64)
+    } else {
+        CrackTime::Centuries
+    }
+}
+
+pub fn analyze(password: &str) -> Analysis {
+    let chars: Zeroizing<Vec<char>> = Zeroizing::new(password.chars().collect());
+    let cardinality = bruteforce_cardinality(&chars);
+
+    let mut matches = Vec::new();
+    dictionary_matches(&chars, &mut matches);
+    keyboard_matches(&chars, &mut matches);
+    sequence_matches(&chars, &mut matches);
+    date_matches(&chars, &mut matches);
+    repeat_matches(&chars, cardinality, &mut matches);
+
+    // best[k] is the cheapest cover of the first k characters, plus the match that ended it.
+    let n = chars.len();
+    let mut best: Vec<(f64, Option<Match>)> = vec![(0.0, None); n + 1];
+    for k in 1..=n {
+        best[k] = (best[k - 1].0 + cardinality.log2(), None);
+        for m in matches.iter().filter(|m| m.end == k) {
+            let cost = best[m.start].0 + m.guesses_log2;
+            if cost < best[k].0 {
+                best[k] = (cost, Some(*m));
+            }
+        }
+    }
+
+    let mut feedback = Vec::new();
+    let mut k = n;
+    while k > 0 {
+        match best[k].1 {
+            Some(m) => {
+                let found = match m.pattern {
+                    Pattern::Dictionary { l33t } => {
+                        if l33t {
+                            push_unique(&mut feedback, Feedback::L33tSubstitution);
+                        }
+                        Feedback::CommonWord
+                    }
+                    Pattern::KeyboardWalk => Feedback::KeyboardWalk,
+                    Pattern::Sequence => Feedback::Sequence,
+                    Pattern::Date => Feedback::Date,
+                    Pattern::Repeat => Feedback::Repeat,
+                };
+                push_unique(&mut feedback, found);
+                k = m.start;
+            }
+            None => k -= 1,
+        }
+    }
+    feedback.reverse();
+    if n < MIN_RECOMMENDED_LENGTH {
+        feedback.push(Feedback::TooShort);
+    }
+
+    Analysis {
+        guesses_log2: best[n].0,
+        feedback,
+    }
+}
+
+fn push_unique(feedback: &mut Vec<Feedback>, item: Feedback) {
+    if !feedback.contains(&item) {
+        feedback.push(item);
+    }
+}
+
+fn bruteforce_cardinality(chars: &[char]) -> f64 {
+    let mut cardinality = 0.0;
+    if chars.iter().any(char::is_ascii_lowercase) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_uppercase) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(char::is_ascii_digit) {
+        cardinality += 10.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_punctuation() || *c == ' ') {
+        cardinality += 33.0;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        cardinality += 100.0;
+    }
+    f64::max(cardinality, 10.0)
+}
+
+fn unl33t(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '8' => 'b',
+        '(' => 'c',
+        '3' => 'e',
+        '6' | '9' => 'g',
+        '1' | '!' => 'i',
+        '|' => 'l',
+        '0' => 'o',
+        '$' | '5' => 's',
+        '7' | '+' => 't',
+        '2' => 'z',
+        _ => c,
+    }
+}
+
+fn word_rank(word: &[char]) -> Option<usize> {
+    COMMON_WORDS
+        .iter()
+        .position(|candidate| candidate.chars().eq(word.iter().copied()))
+        .map(|index| index + 1)
+}
+
+/// Extra guesses needed to also try the capitalisation used in `segment`.
+fn case_variations_log2(segment: &[char]) -> f64 {
+    let upper = segment.iter().filter(|c| c.is_uppercase()).count();
+    let lower = segment.iter().filter(|c| c.is_lowercase()).count();
+    if upper == 0 {
+        0.0
+    } else if lower == 0 || (upper == 1 && segment[0].is_uppercase()) {
+        1.0
+    } else {
+        upper.min(lower) as f64
+    }
+}
+
+fn dictionary_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let lower: Zeroizing<Vec<char>> =
+        Zeroizing::new(chars.iter().flat_map(|c| c.to_lowercase()).collect());
+    if lower.len() != chars.len() {
+        return;
+    }
+    let plain: Zeroizing<Vec<char>> = Zeroizing::new(lower.iter().map(|&c| unl33t(c)).collect());
+
+    for start in 0..chars.len() {
+        let longest = (start + MAX_WORD_LENGTH).min(chars.len());
+        for end in (start + MIN_MATCH_LENGTH)..=longest {
+            let case_log2 = case_variations_log2(&chars[start..end]);
+            if let Some(rank) = word_rank(&lower[start..end]) {
+                matches.push(Match {
+                    start,
+                    end,
+                    guesses_log2: (rank as f64).log2() + case_log2,
+                    pattern: Pattern::Dictionary { l33t: false },
+                });
+            } else if let Some(rank) = word_rank(&plain[start..end]) {
+                let substitutions = lower[start..end]
+                    .iter()
+                    .zip(&plain[start..end])
+                    .filter(|(a, b)| a != b)
+                    .count();
+                matches.push(Match {
+                    start,
+                    end,
+                    guesses_log2: (rank as f64).log2() + case_log2 + substitutions as f64,
+                    pattern: Pattern::Dictionary { l33t: true },
+                });
+            }
+        }
+    }
+}
+
+fn keyboard_position(c: char) -> Option<(usize, usize)> {
+    let c = c.to_ascii_lowercase();
+    KEYBOARD_ROWS
+        .iter()
+        .enumerate()
+        .find_map(|(row, keys)| keys.chars().position(|k| k == c).map(|col| (row, col)))
+}
+
+/// Pushes every maximal run of at least three characters where each step is `step(a, b)`.
+fn push_runs(
+    chars: &[char],
+    step: impl Fn(char, char) -> Option<i32>,
+    guesses_log2: impl Fn(&[char]) -> f64,
+    pattern: Pattern,
+    matches: &mut Vec<Match>,
+) {
+    let mut start = 0;
+    while start + 1 < chars.len() {
+        let direction = step(chars[start], chars[start + 1]);
+        let mut end = start + 1;
+        if direction.is_some() {
+            while end < chars.len() && step(chars[end - 1], chars[end]) == direction {
+                end += 1;
+            }
+        }
+        if end - start >= MIN_MATCH_LENGTH {
+            matches.push(Match {
+                start,
+                end,
+                guesses_log2: guesses_log2(&chars[start..end]),
+                pattern,
+            });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+}
+
+fn keyboard_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let step = |a: char, b: char| match (keyboard_position(a), keyboard_position(b)) {
+        (Some((row_a, col_a)), Some((row_b, col_b))) if row_a == row_b => {
+            let delta = col_b as i32 - col_a as i32;
+            (delta.abs() == 1).then_some(delta)
+        }
+        _ => None,
+    };
+    let guesses = |run: &[char]| (KEYBOARD_KEYS * 2.0 * run.len() as f64).log2() + case_variations_log2(run);
+    push_runs(chars, step, guesses, Pattern::KeyboardWalk, matches);
+}
+
+fn sequence_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let step = |a: char, b: char| {
+        let same_class = (a.is_ascii_lowercase() && b.is_ascii_lowercase())
+            || (a.is_ascii_uppercase() && b.is_ascii_uppercase())
+            || (a.is_ascii_digit() && b.is_ascii_digit());
+        let delta = b as i32 - a as i32;
+        (same_class && delta.abs() == 1).then_some(delta)
+    };
+    let guesses = |run: &[char]| {
+        let first = run[0];
+        let base: f64 = if matches!(first, 'a' | 'A' | 'z' | 'Z' | '0' | '1' | '9') {
+            4.0
+        } else if first.is_ascii_digit() {
+            10.0
+        } else {
+            26.0
+        };
+        let descending = (run[1] as i32) < (run[0] as i32);
+        (base * run.len() as f64 * if descending { 2.0 } else { 1.0 }).log2()
+    };
+    push_runs(chars, step, guesses, Pattern::Sequence, matches);
+}
+
+fn current_year() -> i32 {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    1970 + (seconds / 31_556_952) as i32
+}
+
+fn plausible_date(day: u32, month: u32, year: i32) -> bool {
+    (1..=31).contains(&day) && (1..=12).contains(&month) && (1000..=2099).contains(&year)
+}
+
+fn expand_year(year: u32, digits: usize) -> i32 {
+    match (digits, year) {
+        (2, y) if y > 50 => 1900 + y as i32,
+        (2, y) => 2000 + y as i32,
+        (_, y) => y as i32,
+    }
+}
+
+/// Reads `segment` as a year or a day/month/year date, returning the year.
+fn parse_date(segment: &[char]) -> Option<i32> {
+    let separator = segment.iter().find(|c| !c.is_ascii_digit()).copied();
+    let parts: Vec<&[char]> = match separator {
+        Some(sep) if matches!(sep, '/' | '-' | '.' | '_' | ' ') => segment.split(|&c| c == sep).collect(),
+        Some(_) => return None,
+        None if segment.len() == 4 => {
+            let year: u32 = segment.iter().collect::<String>().parse().ok()?;
+            return (1900..=2099).contains(&year).then_some(year as i32);
+        }
+        None => {
+            // Try the common layouts of undivided dates.
+            let layouts: &[&[usize]] = match segment.len() {
+                6 => &[&[2, 2, 2]],
+                8 => &[&[2, 2, 4], &[4, 2, 2]],
+                _ => return None,
+            };
+            return layouts.iter().find_map(|layout| {
+                let mut offset = 0;
+                let parts: Vec<&[char]> = layout
+                    .iter()
+                    .map(|len| {
+                        let part = &segment[offset..offset + len];
+                        offset += len;
+                        part
+                    })
+                    .collect();
+                parse_date_parts(&parts)
+            });
+        }
+    };
+    if parts.len() == 3 {
+        parse_date_parts(&parts)
+    } else {
+        None
+    }
+}
+
+fn parse_date_parts(parts: &[&[char]]) -> Option<i32> {
+    let numbers: Vec<(u32, usize)> = parts
+        .iter()
+        .map(|part| {
+            let valid = !part.is_empty() && part.len() <= 4 && part.iter().all(char::is_ascii_digit);
+            let value = part.iter().collect::<String>().parse().ok()?;
+            valid.then_some((value, part.len()))
+        })
+        .collect::<Option<_>>()?;
+    let [(a, _), (b, _), (c, c_len)] = [numbers[0], numbers[1], numbers[2]];
+    let (first_len, year_first) = (numbers[0].1, numbers[0].1 == 4);
+
+    if year_first {
+        let year = a as i32;
+        return (plausible_date(c, b, year) || plausible_date(b, c, year)).then_some(year);
+    }
+    let year = expand_year(c, c_len);
+    if first_len <= 2 && (plausible_date(a, b, year) || plausible_date(b, a, year)) {
+        Some(year)
+    } else {
+        None
+    }
+}
+
+fn date_matches(chars: &[char], matches: &mut Vec<Match>) {
+    let reference_year = current_year();
+    for start in 0..chars.len() {
+        if !chars[start].is_ascii_digit() {
+            continue;
+        }
+        for end in (start + 4)..=(start + 10).min(chars.len()) {
+            if !chars[end - 1].is_ascii_digit() {
+                continue;
+            }
+            if let Some(year) = parse_date(&chars[start..end]) {
+                let years = ((year - reference_year).abs() as f64).max(20.0);
+                let days = if end - start == 4 { 1.0 } else { 365.0 };
+                let separator = if chars[start..end].iter().all(char::is_ascii_digit) { 1.0 } else { 4.0 };
+                matches.push(Match {
+                    start,
+                    end,
+                    guesses_log2: (years * days * separator).log2(),
+                    pattern: Pattern::Date,
+                });
+            }
+        }
+    }
+}
+
+fn repeat_matches(chars: &[char], cardinality: f64, matches: &mut Vec<Match>) {
+    for start in 0..chars.len() {
+        for period in 1..=(chars.len() - start) / 2 {
+            let block = &chars[start..start + period];
+            let mut end = start + period;
+            while end + period <= chars.len() && &chars[end..end + period] == block {
+                end += period;
+            }
+            let repeats = (end - start) / period;
+            if repeats >= 2 && end - start >= MIN_MATCH_LENGTH {
+                let block_log2 = period as f64 * cardinality.log2();
+                matches.push(Match {
+                    start,
+                    end,
+                    guesses_log2: block_log2 + (repeats as f64).log2(),
+                    pattern: Pattern::Repeat,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bits(password: &str) -> f64 {
+        analyze(password).guesses_log2
+    }
+
+    #[test]
+    fn common_passwords_are_weak() {
+        assert!(bits("password") < 2.0);
+        assert!(bits("Password") < 3.0);
+        assert!(bits("p@ssw0rd") < 6.0);
+        assert_eq!(
+            analyze("p@ssw0rd").feedback,
+            vec![Feedback::CommonWord, Feedback::L33tSubstitution, Feedback::TooShort]
+        );
+    }
+
+    #[test]
+    fn detects_each_pattern() {
+        let has = |password: &str, feedback: Feedback| analyze(password).feedback.contains(&feedback);
+        assert!(has("xqzqwertyuiop", Feedback::KeyboardWalk));
+        assert!(has("xqzlkjhgfdsa", Feedback::KeyboardWalk));
+        assert!(has("xqzabcdefgh", Feedback::Sequence));
+        assert!(has("xqz98765", Feedback::Sequence));
+        assert!(has("xqz1987", Feedback::Date));
+        assert!(has("xqz24.12.1990", Feedback::Date));
+        assert!(has("xqz19901224", Feedback::Date));
+        assert!(has("xqzaaaaaaa", Feedback::Repeat));
+        assert!(has("xqzkT9kT9kT9", Feedback::Repeat));
+        assert!(has("short", Feedback::TooShort));
+    }
+
+    #[test]
+    fn random_passwords_keep_their_bruteforce_strength() {
+        let analysis = analyze("Kq7#vZ-m2Rx!-pW8nJ");
+        assert!(analysis.guesses_log2 > 100.0, "{}", analysis.guesses_log2);
+        assert!(analysis.feedback.is_empty(), "{:?}", analysis.feedback);
+    }
+
+    #[test]
+    fn patterns_are_cheaper_than_bruteforce() {
+        assert!(bits("qwertyuiopasdf") < bits("qtwyeriuopsadf"));
+        assert!(bits("monkey19901224") < bits("mkneoy92101942"));
+    }
+
+    #[test]
+    fn crack_time_buckets() {
+        assert_eq!(crack_time(10.0), CrackTime::Instant);
+        assert_eq!(crack_time(40.0), CrackTime::Minutes(2));
+        assert_eq!(crack_time(128.0), CrackTime::Centuries);
+    }
+}
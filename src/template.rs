@@ -0,0 +1,367 @@
+//! Filling in translated message templates.
+//!
+//! Templates use a small part of ICU MessageFormat: `{name}` inserts an
+//! argument, and `{name, plural, one {# day} other {# days}}` picks the branch
+//! for the CLDR plural category of a number, with `#` standing for the number
+//! itself. A branch can also match one exact number, e.g. `=0 {none}`.
+//! Numbers are written with the digit grouping of the language. There is no
+//! quoting, so templates cannot contain literal braces.
+
+/// A value for a placeholder.
+#[derive(Clone, Copy, Debug)]
+pub enum Arg<'a> {
+    Number(u64),
+    Text(&'a str),
+}
+
+/// The plural categories defined by CLDR. Most languages only use a few.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword {
+            "zero" => Some(Self::Zero),
+            "one" => Some(Self::One),
+            "two" => Some(Self::Two),
+            "few" => Some(Self::Few),
+            "many" => Some(Self::Many),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+}
+
+/// The CLDR plural category of the whole number `n` in `language`, e.g. `de`.
+/// Languages without rules of their own follow English.
+pub fn plural_category(language: &str, n: u64) -> PluralCategory {
+    match language {
+        "ja" => PluralCategory::Other,
+        "fr" if n <= 1 => PluralCategory::One,
+        "es" | "it" if n == 1 => PluralCategory::One,
+        // Millions take "de"/"di" before the noun
+        "es" | "fr" | "it" if n != 0 && n.is_multiple_of(1_000_000) => PluralCategory::Many,
+        "es" | "fr" | "it" => PluralCategory::Other,
+        _ if n == 1 => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+/// The categories [`plural_category`] can return for `language`, which
+/// every plural in its translations needs a branch for.
+#[cfg(test)]
+pub fn plural_categories(language: &str) -> &'static [PluralCategory] {
+    match language {
+        "ja" => &[PluralCategory::Other],
+        "es" | "fr" | "it" => &[PluralCategory::One, PluralCategory::Many, PluralCategory::Other],
+        _ => &[PluralCategory::One, PluralCategory::Other],
+    }
+}
+
+/// Writes `n` with the digit grouping of `language`.
+pub fn format_number(language: &str, n: u64) -> String {
+    // The separator between groups of three digits, and the number of
+    // digits from which on they are grouped at all
+    let (separator, min_digits) = match language {
+        "de" | "it" => (".", 4),
+        "es" => (".", 5),
+        "fr" => ("\u{202f}", 4),
+        "sv" => ("\u{a0}", 4),
+        _ => (",", 4),
+    };
+    let digits = n.to_string();
+    if digits.len() < min_digits {
+        return digits;
+    }
+    let mut out = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push_str(separator);
+        }
+        out.push(digit);
+    }
+    out
+}
+
+/// Fills in `template` for `language`. Placeholders without a fitting
+/// argument are kept as they are, so the mistake shows.
+pub fn format(language: &str, template: &str, args: &[(&str, Arg)]) -> String {
+    let mut out = String::new();
+    render(language, template, args, None, &mut out);
+    out
+}
+
+/// `number` is what `#` stands for, inside a plural branch.
+fn render(language: &str, template: &str, args: &[(&str, Arg)], number: Option<u64>, out: &mut String) {
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '#']) {
+        out.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        if let Some(after) = tail.strip_prefix('#') {
+            match number {
+                Some(n) => out.push_str(&format_number(language, n)),
+                None => out.push('#'),
+            }
+            rest = after;
+            continue;
+        }
+        let end = match closing_brace(tail) {
+            Some(end) => end,
+            None => {
+                out.push_str(tail);
+                return;
+            }
+        };
+        render_placeholder(language, &tail[..=end], args, out);
+        rest = &tail[end + 1..];
+    }
+    out.push_str(rest);
+}
+
+fn render_placeholder(language: &str, placeholder: &str, args: &[(&str, Arg)], out: &mut String) {
+    let inner = &placeholder[1..placeholder.len() - 1];
+    let (name, spec) = match inner.split_once(',') {
+        Some((name, spec)) => (name.trim(), Some(spec)),
+        None => (inner.trim(), None),
+    };
+    let arg = args.iter().find(|(arg_name, _)| *arg_name == name).map(|(_, arg)| *arg);
+    match (arg, spec) {
+        (Some(Arg::Text(text)), None) => out.push_str(text),
+        (Some(Arg::Number(n)), None) => out.push_str(&format_number(language, n)),
+        (Some(Arg::Number(n)), Some(spec)) => {
+            match plural_branches(spec).and_then(|branches| select_branch(language, n, &branches)) {
+                Some(branch) => render(language, branch, args, Some(n), out),
+                None => out.push_str(placeholder),
+            }
+        }
+        _ => out.push_str(placeholder),
+    }
+}
+
+/// The byte index of the brace that closes the one `text` starts with.
+fn closing_brace(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The selectors and branches of `plural, one {…} other {…}`.
+fn plural_branches(spec: &str) -> Option<Vec<(&str, &str)>> {
+    let (kind, mut rest) = spec.split_once(',')?;
+    if kind.trim() != "plural" {
+        return None;
+    }
+    let mut branches = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return Some(branches);
+        }
+        let start = rest.find('{')?;
+        let end = start + closing_brace(&rest[start..])?;
+        branches.push((rest[..start].trim(), &rest[start + 1..end]));
+        rest = &rest[end + 1..];
+    }
+}
+
+/// An exact match wins over the plural category, `other` is the fallback.
+fn select_branch<'a>(language: &str, n: u64, branches: &[(&str, &'a str)]) -> Option<&'a str> {
+    let exact = format!("={}", n);
+    let category = plural_category(language, n);
+    branches
+        .iter()
+        .find(|(selector, _)| *selector == exact)
+        .or_else(|| {
+            branches
+                .iter()
+                .find(|(selector, _)| PluralCategory::from_keyword(selector) == Some(category))
+        })
+        .or_else(|| branches.iter().find(|(selector, _)| *selector == "other"))
+        .map(|(_, branch)| *branch)
+}
+
+/// The names of all placeholders in `template`, including those inside plural
+/// branches, sorted and without duplicates.
+#[cfg(test)]
+pub fn arguments(template: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = match closing_brace(&rest[start..]) {
+            Some(end) => start + end,
+            None => break,
+        };
+        let inner = &rest[start + 1..end];
+        match inner.split_once(',') {
+            Some((name, spec)) => {
+                names.push(name.trim());
+                for (_, branch) in plural_branches(spec).unwrap_or_default() {
+                    names.extend(arguments(branch));
+                }
+            }
+            None => names.push(inner.trim()),
+        }
+        rest = &rest[end + 1..];
+    }
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// The categories of `language` that some plural in `template` has no
+/// branch for.
+#[cfg(test)]
+pub fn missing_plural_categories(language: &str, template: &str) -> Vec<PluralCategory> {
+    let mut missing = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = match closing_brace(&rest[start..]) {
+            Some(end) => start + end,
+            None => break,
+        };
+        if let Some(branches) = rest[start + 1..end].split_once(',').and_then(|(_, spec)| plural_branches(spec)) {
+            for category in plural_categories(language) {
+                let covered = branches
+                    .iter()
+                    .any(|(selector, _)| PluralCategory::from_keyword(selector) == Some(*category));
+                if !covered && !missing.contains(category) {
+                    missing.push(*category);
+                }
+            }
+            for (_, branch) in branches {
+                for category in missing_plural_categories(language, branch) {
+                    if !missing.contains(&category) {
+                        missing.push(category);
+                    }
+                }
+            }
+        }
+        rest = &rest[end + 1..];
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PluralCategory::{Many, One, Other};
+
+    const DAYS: &str = "{count, plural, one {# day} other {# days}}";
+
+    #[test]
+    fn plural_categories_follow_cldr() {
+        let cases: &[(&str, [PluralCategory; 5])] = &[
+            // 0, 1, 2, 1000, 1000000
+            ("en", [Other, One, Other, Other, Other]),
+            ("de", [Other, One, Other, Other, Other]),
+            ("sv", [Other, One, Other, Other, Other]),
+            ("es", [Other, One, Other, Other, Many]),
+            ("it", [Other, One, Other, Other, Many]),
+            ("fr", [One, One, Other, Other, Many]),
+            ("ja", [Other, Other, Other, Other, Other]),
+        ];
+        for (language, expected) in cases {
+            let actual = [0, 1, 2, 1000, 1_000_000].map(|n| plural_category(language, n));
+            assert_eq!(&actual, expected, "{}", language);
+        }
+    }
+
+    #[test]
+    fn plural_categories_lists_what_the_rules_return() {
+        for language in ["en", "de", "sv", "es", "it", "fr", "ja"] {
+            let mut seen = Vec::new();
+            for n in (0..2000).chain([1_000_000, 2_000_000, 1_000_001]) {
+                let category = plural_category(language, n);
+                assert!(plural_categories(language).contains(&category), "{} {}", language, n);
+                if !seen.contains(&category) {
+                    seen.push(category);
+                }
+            }
+            assert_eq!(seen.len(), plural_categories(language).len(), "{}", language);
+        }
+    }
+
+    #[test]
+    fn numbers_use_the_grouping_of_the_language() {
+        let cases = [
+            ("en", ["999", "1,000", "10,000", "1,234,567"]),
+            ("ja", ["999", "1,000", "10,000", "1,234,567"]),
+            ("de", ["999", "1.000", "10.000", "1.234.567"]),
+            ("it", ["999", "1.000", "10.000", "1.234.567"]),
+            ("es", ["999", "1000", "10.000", "1.234.567"]),
+            ("fr", ["999", "1\u{202f}000", "10\u{202f}000", "1\u{202f}234\u{202f}567"]),
+            ("sv", ["999", "1\u{a0}000", "10\u{a0}000", "1\u{a0}234\u{a0}567"]),
+        ];
+        for (language, expected) in cases {
+            let actual = [999, 1000, 10_000, 1_234_567].map(|n| format_number(language, n));
+            assert_eq!(actual, expected, "{}", language);
+        }
+    }
+
+    #[test]
+    fn plurals_pick_the_branch_of_the_number() {
+        let days = |language: &str, n: u64| format(language, DAYS, &[("count", Arg::Number(n))]);
+        assert_eq!(days("en", 1), "1 day");
+        assert_eq!(days("en", 0), "0 days");
+        assert_eq!(days("en", 1500), "1,500 days");
+        assert_eq!(days("fr", 0), "0 day");
+        assert_eq!(days("ja", 1), "1 days");
+
+        let template = "{count, plural, =0 {none} one {one} many {# many} other {# more}}";
+        let render = |language: &str, n: u64| format(language, template, &[("count", Arg::Number(n))]);
+        assert_eq!(render("en", 0), "none");
+        assert_eq!(render("en", 1), "one");
+        assert_eq!(render("es", 1_000_000), "1.000.000 many");
+        assert_eq!(render("de", 1_000_000), "1.000.000 more");
+    }
+
+    #[test]
+    fn placeholders_are_filled_in_everywhere() {
+        let template = "{name}: {count, plural, one {# file in {folder}} other {# files in {folder}}} #";
+        let args = [("name", Arg::Text("Backup")), ("count", Arg::Number(2)), ("folder", Arg::Text("/tmp"))];
+        assert_eq!(format("en", template, &args), "Backup: 2 files in /tmp #");
+        let args = [("count", Arg::Number(1200)), ("total", Arg::Number(5000))];
+        assert_eq!(format("de", "{count} von {total}", &args), "1.200 von 5.000");
+    }
+
+    #[test]
+    fn unusable_placeholders_are_kept() {
+        assert_eq!(format("en", "Error: {error}", &[]), "Error: {error}");
+        assert_eq!(format("en", DAYS, &[("count", Arg::Text("many"))]), DAYS);
+        assert_eq!(format("en", "{count, select, x {y}}", &[("count", Arg::Number(1))]), "{count, select, x {y}}");
+        assert_eq!(format("en", "{count, plural, one {# day}}", &[("count", Arg::Number(2))]), "{count, plural, one {# day}}");
+        assert_eq!(format("en", "open {brace", &[]), "open {brace");
+    }
+
+    #[test]
+    fn arguments_include_those_inside_plurals() {
+        assert_eq!(arguments("{time}, {bits, plural, one {# bit} other {# bits in {time}}}"), ["bits", "time"]);
+        assert!(arguments("no placeholders").is_empty());
+    }
+
+    #[test]
+    fn missing_categories_are_found() {
+        assert!(missing_plural_categories("de", DAYS).is_empty());
+        assert_eq!(missing_plural_categories("fr", DAYS), [Many]);
+        assert!(missing_plural_categories("ja", "{count, plural, other {#日}}").is_empty());
+        assert_eq!(missing_plural_categories("en", "{count, plural, other {# days}}"), [One]);
+    }
+}
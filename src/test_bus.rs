@@ -0,0 +1,78 @@
+//! A private D-Bus session bus for tests that talk to D-Bus services.
+
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+
+/// A throwaway `dbus-daemon` with one client and one server connection.
+pub struct PrivateBus {
+    daemon: Child,
+    pub client: gio::DBusConnection,
+    pub server: gio::DBusConnection,
+}
+
+impl PrivateBus {
+    /// Starts the daemon and connects to it, stopping it again if that fails.
+    fn start() -> Result<Self, String> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("cannot run dbus-daemon: {}", err))?;
+        match Self::connect(&mut daemon) {
+            Ok((client, server)) => Ok(Self {
+                daemon,
+                client,
+                server,
+            }),
+            Err(err) => {
+                let _ = daemon.kill();
+                let _ = daemon.wait();
+                Err(err)
+            }
+        }
+    }
+
+    fn connect(daemon: &mut Child) -> Result<(gio::DBusConnection, gio::DBusConnection), String> {
+        let stdout = daemon.stdout.take().ok_or("dbus-daemon has no output")?;
+        let mut address = String::new();
+        BufReader::new(stdout)
+            .read_line(&mut address)
+            .map_err(|err| format!("cannot read the bus address: {}", err))?;
+
+        let connect = || {
+            gio::DBusConnection::for_address_sync(
+                address.trim(),
+                gio::DBusConnectionFlags::AUTHENTICATION_CLIENT
+                    | gio::DBusConnectionFlags::MESSAGE_BUS_CONNECTION,
+                None,
+                None::<&gio::Cancellable>,
+            )
+            .map_err(|err| format!("cannot connect to the private bus: {}", err))
+        };
+        Ok((connect()?, connect()?))
+    }
+
+    pub fn server_name(&self) -> String {
+        self.server.unique_name().unwrap().to_string()
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// Runs `test` against a fresh private bus inside its own main context. Fails
+/// when no bus can be started, so a missing `dbus-daemon` cannot pass as a
+/// green test run.
+pub fn run(test: impl FnOnce(&PrivateBus, &glib::MainContext)) {
+    let context = glib::MainContext::new();
+    context
+        .with_thread_default(|| match PrivateBus::start() {
+            Ok(bus) => test(&bus, &context),
+            Err(err) => panic!("D-Bus tests need dbus-daemon: {}", err),
+        })
+        .unwrap();
+}